@@ -0,0 +1,83 @@
+//! Compare the complex slice kernels with a loop over the `Complex` operators.
+//!
+//! Run with `cargo bench --bench complex_kernels`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use grade_processor::gmath::complex::{C32, C64, Complex, ComplexTrait, KernelFloat, get_simd_level,
+                                      mul_conj_elementwise, mul_elementwise, norm_sqr_elementwise,
+                                      scale_elementwise};
+
+const LENGTH: usize = 4096;
+const ITERATIONS: u32 = 20_000;
+
+fn signal<T: KernelFloat>(seed: f64) -> Vec<Complex<T>> {
+    (0..LENGTH)
+        .map(|k| {
+            let x = k as f64 + seed;
+            Complex::new(T::from(x.sin()).unwrap(), T::from(x.cos()).unwrap())
+        })
+        .collect()
+}
+
+fn time<F: FnMut()>(mut run: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        run();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn report(name: &str, operators: Duration, kernel: Duration) {
+    println!("{:<24} operators {:>10.2?}  kernel {:>10.2?}  speedup {:.2}x",
+             name, operators, kernel, operators.as_secs_f64() / kernel.as_secs_f64());
+}
+
+fn bench<T: KernelFloat>(type_name: &str, zero: Complex<T>) {
+    let (a, b) = (signal::<T>(0.0), signal::<T>(1.0));
+    let mut output = vec![zero; LENGTH];
+    let mut norms = vec![T::zero(); LENGTH];
+    let factor = T::from(0.5).unwrap();
+
+    let operators = time(|| {
+        for ((x, y), result) in a.iter().zip(&b).zip(output.iter_mut()) {
+            *result = *x * *y;
+        }
+        black_box(&mut output);
+    });
+    let kernel = time(|| mul_elementwise(black_box(&a), black_box(&b), black_box(&mut output)));
+    report(&format!("mul {}", type_name), operators, kernel);
+
+    let operators = time(|| {
+        for ((x, y), result) in a.iter().zip(&b).zip(output.iter_mut()) {
+            *result = *x * y.conjugate();
+        }
+        black_box(&mut output);
+    });
+    let kernel = time(|| mul_conj_elementwise(black_box(&a), black_box(&b), black_box(&mut output)));
+    report(&format!("mul_conj {}", type_name), operators, kernel);
+
+    let operators = time(|| {
+        for (x, result) in a.iter().zip(norms.iter_mut()) {
+            *result = x.norm_sqr();
+        }
+        black_box(&mut norms);
+    });
+    let kernel = time(|| norm_sqr_elementwise(black_box(&a), black_box(&mut norms)));
+    report(&format!("norm_sqr {}", type_name), operators, kernel);
+
+    let operators = time(|| {
+        for (x, result) in a.iter().zip(output.iter_mut()) {
+            *result = *x * factor;
+        }
+        black_box(&mut output);
+    });
+    let kernel = time(|| scale_elementwise(black_box(&a), factor, black_box(&mut output)));
+    report(&format!("scale {}", type_name), operators, kernel);
+}
+
+fn main() {
+    println!("{} complex values, {:?}", LENGTH, get_simd_level());
+    bench::<f32>("f32", C32::default());
+    bench::<f64>("f64", C64::default());
+}
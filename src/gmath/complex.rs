@@ -1,4 +1,9 @@
-use num_traits::{Float, Zero, One};
+use num_traits::{Float, Inv, Num, Zero, One};
+
+mod kernels;
+
+pub use kernels::{KernelFloat, SimdLevel, get_simd_level, mul_elementwise, mul_conj_elementwise,
+                  norm_sqr_elementwise, scale_elementwise};
 
 
 pub trait ComplexTrait<T: Float> {
@@ -11,14 +16,19 @@ pub trait ComplexTrait<T: Float> {
     fn i() -> Self;
 }
 
+/// `#[repr(C)]` so the layout is two consecutive `T`, real part first, the
+/// same as `[T; 2]` and `num_complex::Complex<T>`.
+///
+/// With the `serde` feature it is serialized as the pair `[real, imag]`.
 #[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
 pub struct Complex<T : Float + std::fmt::Display + std::fmt::Debug> {
     pub real: T,
     pub imag: T,
 }
 
-type C64 = Complex<f64>;
-type C32 = Complex<f32>;
+pub type C64 = Complex<f64>;
+pub type C32 = Complex<f32>;
 
 impl<T: Float + std::fmt::Display + std::fmt::Debug> std::fmt::Display for Complex<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -46,7 +56,7 @@ impl<T: Float + std::fmt::Debug + std::fmt::Display> ComplexTrait<T> for Complex
     }
 
     fn magnitude(&self) -> T {
-        (self.real * self.real + self.imag * self.imag).sqrt()
+        self.real.hypot(self.imag)
     }
 
     fn phase(&self) -> T {
@@ -134,21 +144,24 @@ impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::MulAssign for Com
 impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::Div for Complex<T> {
     type Output = Self;
 
+    /// Smith's algorithm: dividing through by the larger component of the
+    /// divisor avoids the overflow and underflow of `re² + im²`.
     fn div(self, other: Self) -> Self {
-        let denom = other.real * other.real + other.imag * other.imag;
-        let real = (self.real * other.real + self.imag * other.imag) / denom;
-        let imag = (self.imag * other.real - self.real * other.imag) / denom;
-        Complex::new(real, imag)
+        if other.real.abs() >= other.imag.abs() {
+            let ratio = other.imag / other.real;
+            let denom = other.real + other.imag * ratio;
+            Complex::new((self.real + self.imag * ratio) / denom, (self.imag - self.real * ratio) / denom)
+        } else {
+            let ratio = other.real / other.imag;
+            let denom = other.real * ratio + other.imag;
+            Complex::new((self.real * ratio + self.imag) / denom, (self.imag * ratio - self.real) / denom)
+        }
     }
 
 }
 impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::DivAssign for Complex<T> {
     fn div_assign(&mut self, other: Self) {
-        let denom = other.real * other.real + other.imag * other.imag;
-        let real = (self.real * other.real + self.imag * other.imag) / denom;
-        let imag = (self.imag * other.real - self.real * other.imag) / denom;
-        self.real = real;
-        self.imag = imag;
+        *self = *self / other;
     }
 }
 
@@ -160,12 +173,322 @@ impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::Neg for Complex<T
     }
 }
 
+impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::Rem for Complex<T> {
+    type Output = Self;
+
+    /// Remainder of the division truncated to a Gaussian integer quotient.
+    fn rem(self, other: Self) -> Self {
+        let quotient = self / other;
+        self - other * Complex::new(quotient.real.trunc(), quotient.imag.trunc())
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::RemAssign for Complex<T> {
+    fn rem_assign(&mut self, other: Self) {
+        *self = *self % other;
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::Add<T> for Complex<T> {
+    type Output = Self;
+
+    fn add(self, other: T) -> Self {
+        Complex::new(self.real + other, self.imag)
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::AddAssign<T> for Complex<T> {
+    fn add_assign(&mut self, other: T) {
+        self.real = self.real + other;
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::Sub<T> for Complex<T> {
+    type Output = Self;
+
+    fn sub(self, other: T) -> Self {
+        Complex::new(self.real - other, self.imag)
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::SubAssign<T> for Complex<T> {
+    fn sub_assign(&mut self, other: T) {
+        self.real = self.real - other;
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::Mul<T> for Complex<T> {
+    type Output = Self;
+
+    fn mul(self, other: T) -> Self {
+        Complex::new(self.real * other, self.imag * other)
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::MulAssign<T> for Complex<T> {
+    fn mul_assign(&mut self, other: T) {
+        self.real = self.real * other;
+        self.imag = self.imag * other;
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::Div<T> for Complex<T> {
+    type Output = Self;
+
+    fn div(self, other: T) -> Self {
+        Complex::new(self.real / other, self.imag / other)
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> std::ops::DivAssign<T> for Complex<T> {
+    fn div_assign(&mut self, other: T) {
+        self.real = self.real / other;
+        self.imag = self.imag / other;
+    }
+}
+
+/// `scalar op Complex` for the primitive float types, which the orphan rule
+/// doesn't allow generically.
+macro_rules! impl_scalar_lhs_ops {
+    ($($scalar:ty),*) => {$(
+        impl std::ops::Add<Complex<$scalar>> for $scalar {
+            type Output = Complex<$scalar>;
+
+            fn add(self, other: Complex<$scalar>) -> Complex<$scalar> {
+                Complex::new(self + other.real, other.imag)
+            }
+        }
+
+        impl std::ops::Sub<Complex<$scalar>> for $scalar {
+            type Output = Complex<$scalar>;
+
+            fn sub(self, other: Complex<$scalar>) -> Complex<$scalar> {
+                Complex::new(self - other.real, -other.imag)
+            }
+        }
+
+        impl std::ops::Mul<Complex<$scalar>> for $scalar {
+            type Output = Complex<$scalar>;
+
+            fn mul(self, other: Complex<$scalar>) -> Complex<$scalar> {
+                Complex::new(self * other.real, self * other.imag)
+            }
+        }
+
+        impl std::ops::Div<Complex<$scalar>> for $scalar {
+            type Output = Complex<$scalar>;
+
+            fn div(self, other: Complex<$scalar>) -> Complex<$scalar> {
+                Complex::from(self) / other
+            }
+        }
+    )*};
+}
+
+impl_scalar_lhs_ops!(f32, f64);
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> std::iter::Sum for Complex<T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Complex::zero(), |sum, value| sum + value)
+    }
+}
+
+impl<'a, T: Float + std::fmt::Display + std::fmt::Debug> std::iter::Sum<&'a Complex<T>> for Complex<T> {
+    fn sum<I: Iterator<Item = &'a Complex<T>>>(iter: I) -> Self {
+        iter.fold(Complex::zero(), |sum, value| sum + *value)
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> std::iter::Product for Complex<T> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Complex::one(), |product, value| product * value)
+    }
+}
+
+impl<'a, T: Float + std::fmt::Display + std::fmt::Debug> std::iter::Product<&'a Complex<T>> for Complex<T> {
+    fn product<I: Iterator<Item = &'a Complex<T>>>(iter: I) -> Self {
+        iter.fold(Complex::one(), |product, value| product * *value)
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> Inv for Complex<T> {
+    type Output = Self;
+
+    fn inv(self) -> Self {
+        Complex::one() / self
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> Default for Complex<T> {
+    fn default() -> Self {
+        Complex::zero()
+    }
+}
+
 impl<T: Float + std::fmt::Display + std::fmt::Debug> From<T> for Complex<T> {
     fn from(value: T) -> Self {
         Complex::new(value, T::zero())
     }
 }
 
+#[cfg(feature = "num-complex")]
+impl<T: Float + std::fmt::Display + std::fmt::Debug> From<num_complex::Complex<T>> for Complex<T> {
+    fn from(value: num_complex::Complex<T>) -> Self {
+        Complex::new(value.re, value.im)
+    }
+}
+
+#[cfg(feature = "num-complex")]
+impl<T: Float + std::fmt::Display + std::fmt::Debug> From<Complex<T>> for num_complex::Complex<T> {
+    fn from(value: Complex<T>) -> Self {
+        num_complex::Complex::new(value.real, value.imag)
+    }
+}
+
+/// View `num_complex` values as `Complex` without copying.
+#[cfg(feature = "num-complex")]
+pub fn from_num_complex_slice<T: Float + std::fmt::Display + std::fmt::Debug>(values: &[num_complex::Complex<T>]) -> &[Complex<T>] {
+    // SAFETY: both types are #[repr(C)] structs of two `T`, real part first.
+    unsafe { std::slice::from_raw_parts(values.as_ptr() as *const Complex<T>, values.len()) }
+}
+
+#[cfg(feature = "num-complex")]
+pub fn from_num_complex_slice_mut<T: Float + std::fmt::Display + std::fmt::Debug>(values: &mut [num_complex::Complex<T>]) -> &mut [Complex<T>] {
+    // SAFETY: see `from_num_complex_slice`.
+    unsafe { std::slice::from_raw_parts_mut(values.as_mut_ptr() as *mut Complex<T>, values.len()) }
+}
+
+/// View `Complex` values as `num_complex` ones without copying.
+#[cfg(feature = "num-complex")]
+pub fn as_num_complex_slice<T: Float + std::fmt::Display + std::fmt::Debug>(values: &[Complex<T>]) -> &[num_complex::Complex<T>] {
+    // SAFETY: see `from_num_complex_slice`.
+    unsafe { std::slice::from_raw_parts(values.as_ptr() as *const num_complex::Complex<T>, values.len()) }
+}
+
+#[cfg(feature = "num-complex")]
+pub fn as_num_complex_slice_mut<T: Float + std::fmt::Display + std::fmt::Debug>(values: &mut [Complex<T>]) -> &mut [num_complex::Complex<T>] {
+    // SAFETY: see `from_num_complex_slice`.
+    unsafe { std::slice::from_raw_parts_mut(values.as_mut_ptr() as *mut num_complex::Complex<T>, values.len()) }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Float + std::fmt::Display + std::fmt::Debug + serde::Serialize> serde::Serialize for Complex<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.real, self.imag).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Float + std::fmt::Display + std::fmt::Debug + serde::Deserialize<'de>> serde::Deserialize<'de> for Complex<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (real, imag) = <(T, T)>::deserialize(deserializer)?;
+        Ok(Complex::new(real, imag))
+    }
+}
+
+/// View an interleaved `[re, im, re, im, ...]` buffer as complex samples
+/// without copying. `None` if the buffer has an odd length.
+pub fn from_interleaved<T: Float + std::fmt::Display + std::fmt::Debug>(samples: &[T]) -> Option<&[Complex<T>]> {
+    if !samples.len().is_multiple_of(2) {
+        return None;
+    }
+    // SAFETY: `Complex<T>` is #[repr(C)] with two `T` fields, so it has the
+    // size of `[T; 2]` and the alignment of `T`.
+    Some(unsafe { std::slice::from_raw_parts(samples.as_ptr() as *const Complex<T>, samples.len() / 2) })
+}
+
+pub fn from_interleaved_mut<T: Float + std::fmt::Display + std::fmt::Debug>(samples: &mut [T]) -> Option<&mut [Complex<T>]> {
+    if !samples.len().is_multiple_of(2) {
+        return None;
+    }
+    // SAFETY: see `from_interleaved`.
+    Some(unsafe { std::slice::from_raw_parts_mut(samples.as_mut_ptr() as *mut Complex<T>, samples.len() / 2) })
+}
+
+/// View complex samples as an interleaved `[re, im, ...]` buffer without copying.
+pub fn as_interleaved<T: Float + std::fmt::Display + std::fmt::Debug>(values: &[Complex<T>]) -> &[T] {
+    // SAFETY: see `from_interleaved`.
+    unsafe { std::slice::from_raw_parts(values.as_ptr() as *const T, values.len() * 2) }
+}
+
+/// Convert interleaved 16 bit fixed-point I/Q samples, multiplying each
+/// component by `scale` (`1.0 / 32768.0` maps full scale to [-1, 1)).
+/// A trailing unpaired sample is ignored.
+pub fn from_iq_i16(samples: &[i16], scale: f32) -> Vec<C32> {
+    samples.chunks_exact(2)
+        .map(|iq| Complex::new(iq[0] as f32 * scale, iq[1] as f32 * scale))
+        .collect()
+}
+
+/// Convert interleaved 8 bit fixed-point I/Q samples, multiplying each
+/// component by `scale` (`1.0 / 128.0` maps full scale to [-1, 1)).
+/// A trailing unpaired sample is ignored.
+pub fn from_iq_i8(samples: &[i8], scale: f32) -> Vec<C32> {
+    samples.chunks_exact(2)
+        .map(|iq| Complex::new(iq[0] as f32 * scale, iq[1] as f32 * scale))
+        .collect()
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> Num for Complex<T> {
+    type FromStrRadixErr = String;
+
+    fn from_str_radix(text: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        parse_complex(text, radix, |part| T::from_str_radix(part, radix).ok())
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug + std::str::FromStr> std::str::FromStr for Complex<T> {
+    type Err = String;
+
+    /// Parse `a+bi`, `a-bi`, `a`, `bi` or `i` (`j` is accepted for `i`).
+    /// Whitespace is ignored, so the `Display` output parses back.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_complex(s, 10, |part| part.parse::<T>().ok())
+    }
+}
+
+fn parse_complex<T, F>(text: &str, radix: u32, parse_part: F) -> Result<Complex<T>, String>
+where
+    T: Float + std::fmt::Display + std::fmt::Debug,
+    F: Fn(&str) -> Option<T>,
+{
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let error = || format!("Invalid complex number {}", text);
+    let bytes = compact.as_bytes();
+    // First sign after the leading one that doesn't belong to a decimal exponent.
+    let split = (1..bytes.len()).find(|&index| {
+        (bytes[index] == b'+' || bytes[index] == b'-')
+            && !(radix == 10 && matches!(bytes[index - 1], b'e' | b'E'))
+    });
+    let parse_part = |part: &str| parse_part(part.strip_prefix('+').unwrap_or(part));
+    let (real, imag) = match split {
+        Some(index) => {
+            let imag = &compact[index..];
+            // "a+-bi" as written by `Display` for a negative imaginary part.
+            let imag = match imag.strip_prefix('+') {
+                Some(rest) if rest.starts_with(['+', '-']) => rest,
+                _ => imag,
+            };
+            (&compact[..index], Some(imag))
+        }
+        None if compact.ends_with(['i', 'j']) => ("", Some(compact.as_str())),
+        None => (compact.as_str(), None),
+    };
+    let real = if real.is_empty() { T::zero() } else { parse_part(real).ok_or_else(error)? };
+    let imag = match imag {
+        Some(imag) => {
+            let coefficient = imag.strip_suffix(['i', 'j']).ok_or_else(error)?;
+            match coefficient {
+                "" | "+" => T::one(),
+                "-" => -T::one(),
+                coefficient => parse_part(coefficient).ok_or_else(error)?,
+            }
+        }
+        None => T::zero(),
+    };
+    Ok(Complex::new(real, imag))
+}
+
 impl<T: Float + std::fmt::Display + std::fmt::Debug> Complex<T> {
     pub fn from_polar(magnitude: T, phase: T) -> Self {
         Complex::new(magnitude * phase.cos(), magnitude * phase.sin())
@@ -179,10 +502,61 @@ impl<T: Float + std::fmt::Display + std::fmt::Debug> Complex<T> {
         self.magnitude()
     }
 
+    /// Squared magnitude `re² + im²`, cheaper than `magnitude` when only
+    /// comparing or accumulating powers.
+    pub fn norm_sqr(&self) -> T {
+        self.real * self.real + self.imag * self.imag
+    }
+
+    /// Principal square root, computed directly from the components.
+    ///
+    /// Branch cut on the negative real axis, the sign of a zero imaginary
+    /// part selects the side: `sqrt(-1 + 0i) = i`, `sqrt(-1 - 0i) = -i`.
+    /// Infinite and NaN components follow C99 `csqrt`: `sqrt(x ± i∞) = ∞ ± i∞`
+    /// for any x, `sqrt(-∞ ± iy) = 0 ± i∞`, `sqrt(+∞ ± iy) = ∞ ± 0i`, and
+    /// a NaN anywhere else gives NaN.
     pub fn sqrt(&self) -> Self {
-        let r = self.magnitude().sqrt();
-        let theta = self.phase() / T::from(2.0).unwrap();
-        Complex::from_polar(r, theta)
+        if self.is_zero() {
+            return Complex::new(T::zero(), self.imag);
+        }
+        if self.imag.is_infinite() {
+            return Complex::new(T::infinity(), self.imag);
+        }
+        if self.real.is_infinite() {
+            if self.imag.is_nan() {
+                return if self.real > T::zero() {
+                    Complex::new(T::infinity(), T::nan())
+                } else {
+                    Complex::new(T::nan(), T::infinity())
+                };
+            }
+            return if self.real > T::zero() {
+                Complex::new(T::infinity(), T::zero().copysign(self.imag))
+            } else {
+                Complex::new(T::zero(), T::infinity().copysign(self.imag))
+            };
+        }
+        if self.real.is_nan() || self.imag.is_nan() {
+            return Complex::new(T::nan(), T::nan());
+        }
+        let two = T::from(2.0).unwrap();
+        let four = two * two;
+        // Rescale by exact powers of two so neither `magnitude` overflows
+        // nor halving the components loses subnormal bits.
+        let largest = self.real.abs().max(self.imag.abs());
+        if largest > T::max_value() / four {
+            return (*self / four).sqrt() * two;
+        }
+        if largest < T::min_positive_value() * four {
+            let epsilon = T::epsilon();
+            return (*self / (epsilon * epsilon)).sqrt() * epsilon;
+        }
+        let t = (self.real.abs() / two + self.magnitude() / two).sqrt();
+        if self.real >= T::zero() {
+            Complex::new(t, self.imag / (two * t))
+        } else {
+            Complex::new(self.imag.abs() / (two * t), t.copysign(self.imag))
+        }
     }
 
     pub fn powi(&self, exponent: i32) -> Self {
@@ -218,20 +592,32 @@ impl<T: Float + std::fmt::Display + std::fmt::Debug> Complex<T> {
         Complex::new(self.real.sin() * self.imag.cosh(), self.real.cos() * self.imag.sinh())
     }
 
+    /// Principal arcsine, `-i asinh(iz)`.
+    ///
+    /// Branch cuts on the real axis outside [-1, 1]; the real part of the
+    /// result is in [-π/2, π/2]. On the cuts the imaginary part takes the
+    /// sign of the imaginary zero: `asin(2 + 0i) = π/2 + i acosh 2`,
+    /// `asin(2 - 0i) = π/2 - i acosh 2`.
     pub fn asin(&self) -> Self {
-        let r = self.magnitude();
-        let theta = self.phase();
-        Complex::new(theta.sin() / r, theta.cos() / r)
+        // Rotate by hand, multiplying by `i` would lose the sign of a zero.
+        let w = Complex::new(-self.imag, self.real).asinh();
+        Complex::new(w.imag, -w.real)
     }
 
     pub fn cos(&self) -> Self {
         Complex::new(self.real.cos() * self.imag.cosh(), -self.real.sin() * self.imag.sinh())
     }
 
+    /// Principal arccosine, `π/2 - asin(z)`.
+    ///
+    /// Branch cuts on the real axis outside [-1, 1]; the real part of the
+    /// result is in [0, π]. On the cuts the imaginary part has the opposite
+    /// sign to the imaginary zero: `acos(2 + 0i) = -i acosh 2`,
+    /// `acos(2 - 0i) = i acosh 2`.
     pub fn acos(&self) -> Self {
-        let r = self.magnitude();
-        let theta = self.phase();
-        Complex::new(theta.cos() / r, -theta.sin() / r)
+        let w = self.asin();
+        let half_pi = T::from(std::f64::consts::FRAC_PI_2).unwrap();
+        Complex::new(half_pi - w.real, -w.imag)
     }
 
     pub fn tan(&self) -> Self {
@@ -242,36 +628,99 @@ impl<T: Float + std::fmt::Display + std::fmt::Debug> Complex<T> {
         self.sin() / denom
     }
 
+    /// Principal arctangent, `(ln(1 + iz) - ln(1 - iz)) / 2i`.
+    ///
+    /// Branch cuts on the imaginary axis outside [-i, i]; the real part of
+    /// the result is in [-π/2, π/2]. `atan(±i)` is `±i∞`.
     pub fn atan(&self) -> Self {
-        let r = self.magnitude();
-        let theta = self.phase();
-        Complex::new(theta.sin() / r, theta.cos() / r)
+        let i = Complex::<T>::i();
+        if *self == i {
+            return Complex::new(T::zero(), T::infinity());
+        }
+        if *self == -i {
+            return Complex::new(T::zero(), T::neg_infinity());
+        }
+        let two_i = Complex::new(T::zero(), T::from(2.0).unwrap());
+        ((Complex::one() + i * *self).ln() - (Complex::one() - i * *self).ln()) / two_i
     }
 
-    pub fn atan2(y: T, x: T) -> Self {
-        let r = (x * x + y * y).sqrt();
-        let theta = y.atan2(x);
-        Complex::new(r, theta)
+    /// Two argument arctangent `-i ln((x + iy) / sqrt(x² + y²))`.
+    ///
+    /// For real `y` and `x` this is the angle of the point (x, y), in
+    /// (-π, π], like `f64::atan2`. Where x² + y² = 0 with non-real arguments
+    /// the result is singular: `i∞` for y = ix and `-i∞` for y = -ix, like
+    /// `atan(±i)`.
+    ///
+    /// This used to take real `y` and `x` and return the polar pair
+    /// `(sqrt(x² + y²), atan2(y, x))` packed in a `Complex`. Callers wanting
+    /// that should use `Complex::new(x.hypot(y), y.atan2(x))`.
+    pub fn atan2(y: Self, x: Self) -> Self {
+        let i = Complex::<T>::i();
+        let point = x + i * y;
+        let radius = (x * x + y * y).sqrt();
+        if radius.is_zero() {
+            if y.imag.is_zero() && x.imag.is_zero() {
+                return Complex::new(y.real.atan2(x.real), T::zero());
+            }
+            let imag = if point.is_zero() { T::infinity() } else { T::neg_infinity() };
+            return Complex::new(T::zero(), imag);
+        }
+        -i * (point / radius).ln()
     }
 
     pub fn sinh(&self) -> Self {
         Complex::new(self.real.sinh() * self.imag.cos(), self.real.cosh() * self.imag.sin())
     }
 
+    /// Principal inverse hyperbolic sine, `ln(z + sqrt(z² + 1))`.
+    ///
+    /// Branch cuts on the imaginary axis outside [-i, i]; the imaginary
+    /// part of the result is in [-π/2, π/2]. On the cuts the real part
+    /// takes the sign of the real zero.
     pub fn asinh(&self) -> Self {
-        let r = self.magnitude();
-        let theta = self.phase();
-        Complex::new(theta.sinh() / r, theta.cosh() / r)
+        // Reduce to the first quadrant, where `z + sqrt(z² + 1)` does not
+        // cancel, with asinh(-z) = -asinh(z) and asinh(z̄) = conj(asinh(z)).
+        if self.real.is_sign_negative() {
+            return -(-*self).asinh();
+        }
+        if self.imag.is_sign_negative() {
+            return self.conjugate().asinh().conjugate();
+        }
+        let threshold = T::epsilon().sqrt();
+        let largest = self.real.max(self.imag);
+        if largest < threshold {
+            return *self;
+        }
+        if largest > T::one() / threshold {
+            // z² + 1 is z² to working precision, and may overflow.
+            let ln_two = T::from(std::f64::consts::LN_2).unwrap();
+            return self.ln() + ln_two;
+        }
+        (*self + (*self * *self + Complex::one()).sqrt()).ln()
     }
 
     pub fn cosh(&self) -> Self {
         Complex::new(self.real.cosh() * self.imag.cos(), self.real.sinh() * self.imag.sin())
     }
 
+    /// Principal inverse hyperbolic cosine,
+    /// `2 ln(sqrt((z + 1) / 2) + sqrt((z - 1) / 2))`.
+    ///
+    /// Branch cut on the real axis below 1; the real part of the result is
+    /// non-negative and the imaginary part is in [-π, π]. On the cut the
+    /// imaginary part takes the sign of the imaginary zero:
+    /// `acosh(-2 + 0i) = acosh 2 + iπ`, `acosh(-2 - 0i) = acosh 2 - iπ`.
     pub fn acosh(&self) -> Self {
-        let r = self.magnitude();
-        let theta = self.phase();
-        Complex::new(theta.cosh() / r, theta.sinh() / r)
+        // Adding one would turn a -0 imaginary part into +0, so take the
+        // lower half plane from acosh(z̄) = conj(acosh(z)).
+        if self.imag.is_sign_negative() {
+            return self.conjugate().acosh().conjugate();
+        }
+        let two = T::from(2.0).unwrap();
+        let half = T::one() / two;
+        let plus = ((*self + Complex::one()) * half).sqrt();
+        let minus = ((*self - Complex::one()) * half).sqrt();
+        (plus + minus).ln() * two
     }
 
     pub fn tanh(&self) -> Self {
@@ -282,9 +731,335 @@ impl<T: Float + std::fmt::Display + std::fmt::Debug> Complex<T> {
         self.sinh() / denom
     }
 
+    /// Principal inverse hyperbolic tangent, `(ln(1 + z) - ln(1 - z)) / 2`.
+    ///
+    /// Branch cuts on the real axis outside [-1, 1]; the imaginary part of
+    /// the result is in [-π/2, π/2]. On the cuts the imaginary part takes the
+    /// sign of the imaginary zero: `atanh(2 + 0i) = acoth 2 + iπ/2`,
+    /// `atanh(2 - 0i) = acoth 2 - iπ/2`. `atanh(±1)` is `±∞`.
     pub fn atanh(&self) -> Self {
-        let r = self.magnitude();
-        let theta = self.phase();
-        Complex::new(theta.sinh() / r, theta.cosh() / r)
+        let one = T::one();
+        let two = one + one;
+        let half_pi = T::from(std::f64::consts::FRAC_PI_2).unwrap();
+        let (x, y) = (self.real, self.imag);
+        if x.abs().max(y.abs()) > one / T::epsilon().sqrt() {
+            // atanh(z) = ±iπ/2 + 1/z + O(1/z³).
+            let inverse = self.inv();
+            return Complex::new(inverse.real, half_pi.copysign(y) + inverse.imag);
+        }
+        // Real part ln(|1 + z|² / |1 - z|²) / 4 written with ln_1p, so small
+        // arguments keep their precision; imaginary part arg((1 + z)(1 - z̄)) / 2.
+        let denominator = (one - x) * (one - x) + y * y;
+        let real = (two * two * x / denominator).ln_1p() / (two * two);
+        let imag = (two * y).atan2((one - x) * (one + x) - y * y) / two;
+        Complex::new(real, imag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    fn assert_close(actual: C64, expected: C64) {
+        let tolerance = 1e-9 * (1.0 + expected.magnitude());
+        assert!((actual - expected).magnitude() < tolerance, "{:?} != {:?}", actual, expected);
+    }
+
+    /// Grid over [-3, 3] x [-3, 3], staying off the axes where the branch cuts lie.
+    fn grid() -> Vec<C64> {
+        let steps = [-3.0, -1.7, -1.0, -0.45, -0.1, 0.1, 0.45, 1.0, 1.7, 3.0];
+        steps.iter()
+            .flat_map(|real| steps.iter().map(move |imag| C64::new(*real, *imag)))
+            .collect()
+    }
+
+    #[test]
+    fn test_inverse_trigonometric_identities() {
+        for z in grid() {
+            assert_close(z.asin().sin(), z);
+            assert_close(z.acos().cos(), z);
+            assert_close(z.atan().tan(), z);
+            assert!(z.asin().real.abs() <= FRAC_PI_2);
+            assert!((0.0..=PI).contains(&z.acos().real));
+            assert!(z.atan().real.abs() <= FRAC_PI_2);
+            assert_close(z.asin() + z.acos(), C64::new(FRAC_PI_2, 0.0));
+        }
+    }
+
+    #[test]
+    fn test_inverse_hyperbolic_identities() {
+        for z in grid() {
+            assert_close(z.asinh().sinh(), z);
+            assert_close(z.acosh().cosh(), z);
+            assert_close(z.atanh().tanh(), z);
+            assert!(z.asinh().imag.abs() <= FRAC_PI_2);
+            assert!(z.acosh().real >= 0.0 && z.acosh().imag.abs() <= PI);
+            assert!(z.atanh().imag.abs() <= FRAC_PI_2);
+        }
+    }
+
+    #[test]
+    fn test_inverse_functions_on_real_axis() {
+        for x in [-1.0, -0.6, 0.0, 0.3, 1.0] {
+            assert_close(C64::from(x).asin(), C64::from(f64::asin(x)));
+            assert_close(C64::from(x).acos(), C64::from(f64::acos(x)));
+        }
+        for x in [-20.0, -0.6, 0.0, 0.3, 5.0] {
+            assert_close(C64::from(x).atan(), C64::from(f64::atan(x)));
+            assert_close(C64::from(x).asinh(), C64::from(f64::asinh(x)));
+        }
+        assert_close(C64::from(2.0).acosh(), C64::from(f64::acosh(2.0)));
+        assert_close(C64::from(0.5).atanh(), C64::from(f64::atanh(0.5)));
+        assert_close(C64::from(2.0).asin(), C64::new(FRAC_PI_2, f64::acosh(2.0)));
+        assert_eq!(C64::i().atan().imag, f64::INFINITY);
+        assert_eq!(C64::from(1.0).atanh().real, f64::INFINITY);
+        assert_eq!(C64::from(-1.0).atanh().real, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_inverse_functions_on_branch_cuts() {
+        let acosh_2 = f64::acosh(2.0);
+        let acoth_2 = f64::atanh(0.5);
+        for sign in [1.0, -1.0] {
+            let above = |real: f64| C64::new(real, 0.0f64.copysign(sign));
+            assert_close(above(2.0).asin(), C64::new(FRAC_PI_2, sign * acosh_2));
+            assert_close(above(-2.0).asin(), C64::new(-FRAC_PI_2, sign * acosh_2));
+            assert_close(above(2.0).acos(), C64::new(0.0, -sign * acosh_2));
+            assert_close(above(-2.0).acos(), C64::new(PI, -sign * acosh_2));
+            assert_close(above(-2.0).acosh(), C64::new(acosh_2, sign * PI));
+            assert_close(above(0.5).acosh(), C64::new(0.0, sign * f64::acos(0.5)));
+            assert_close(above(2.0).atanh(), C64::new(acoth_2, sign * FRAC_PI_2));
+            assert_close(above(-2.0).atanh(), C64::new(-acoth_2, sign * FRAC_PI_2));
+            let left = C64::new(0.0f64.copysign(sign), 2.0);
+            assert_close(left.asinh(), C64::new(sign * acosh_2, FRAC_PI_2));
+        }
+        // Inside the cuts a zero imaginary part keeps its sign.
+        assert!(C64::new(0.5, -0.0).asin().imag.is_sign_negative());
+        assert!(C64::new(0.5, 0.0).acos().imag.is_sign_negative());
+        assert!(C64::new(0.5, -0.0).atanh().imag.is_sign_negative());
+    }
+
+    #[test]
+    fn test_inverse_functions_at_extreme_magnitudes() {
+        let ln_2e200 = std::f64::consts::LN_2 + 200.0 * std::f64::consts::LN_10;
+        assert_close(C64::from(1e200).asin(), C64::new(FRAC_PI_2, ln_2e200));
+        assert_close(C64::from(1e200).acos(), C64::new(0.0, -ln_2e200));
+        assert_close(C64::from(1e200).asinh(), C64::from(ln_2e200));
+        assert_close(C64::from(-1e200).asinh(), C64::from(-ln_2e200));
+        assert_close(C64::from(1e200).acosh(), C64::from(ln_2e200));
+        assert_close(C64::new(0.0, 1e200).asinh(), C64::new(ln_2e200, FRAC_PI_2));
+        assert_close(C64::from(-1e8).asinh(), C64::from(f64::asinh(-1e8)));
+        let far = C64::new(1e300, -1e300).atanh();
+        assert!((far.real - 5e-301).abs() < 1e-310);
+        assert_close(far, C64::new(0.0, -FRAC_PI_2));
+        assert_close(C64::from(1e8).atanh(), C64::new(1e-8, FRAC_PI_2));
+
+        for z in [C64::new(1e-17, 0.0), C64::new(-3e-20, 2e-20), C64::new(0.0, -1e-300)] {
+            for w in [z.asin(), z.asinh(), z.atanh()] {
+                assert!((w - z).magnitude() <= 1e-15 * z.magnitude(), "{:?} != {:?}", w, z);
+            }
+        }
+        assert_eq!(C64::from(1e-17).atanh(), C64::from(1e-17));
+        assert_close(C64::from(1e-17).acos(), C64::from(FRAC_PI_2));
+        // atanh(z) - z = z³/3 + O(z⁵), which cancellation in ln would swamp.
+        let z = C64::new(1e-5, 2e-6);
+        let cubic = (z.atanh() - z - z * z * z / 3.0).magnitude();
+        assert!(cubic < 1e-15 * z.magnitude(), "{}", cubic);
+    }
+
+    #[test]
+    fn test_scalar_operations() {
+        let z = C64::new(3.0, -4.0);
+        assert_eq!(z * 2.0, C64::new(6.0, -8.0));
+        assert_eq!(2.0 * z, C64::new(6.0, -8.0));
+        assert_eq!(z / 2.0, C64::new(1.5, -2.0));
+        assert_eq!(z + 1.0, C64::new(4.0, -4.0));
+        assert_eq!(1.0 - z, C64::new(-2.0, 4.0));
+        assert_close(25.0 / z, C64::new(3.0, 4.0));
+        assert_eq!(2.0f32 * Complex::new(1.0f32, 1.0), Complex::new(2.0f32, 2.0));
+        let mut w = z;
+        w *= 2.0;
+        w /= 4.0;
+        w -= 1.5;
+        assert_eq!(w, C64::new(0.0, -2.0));
+        assert_close(z.inv(), C64::new(0.12, 0.16));
+        assert_eq!(C64::default(), C64::zero());
+    }
+
+    #[test]
+    fn test_sum_and_product() {
+        let values = [C64::new(1.0, 1.0), C64::new(2.0, -1.0), C64::new(0.0, 3.0)];
+        assert_eq!(values.iter().sum::<C64>(), C64::new(3.0, 3.0));
+        assert_eq!(values.iter().copied().sum::<C64>(), C64::new(3.0, 3.0));
+        assert_eq!(values.iter().product::<C64>(), C64::new(-3.0, 9.0));
+        assert_eq!(Vec::<C64>::new().into_iter().product::<C64>(), C64::one());
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("1+2i".parse::<C64>(), Ok(C64::new(1.0, 2.0)));
+        assert_eq!(" -1.5 - 2.5i ".parse::<C64>(), Ok(C64::new(-1.5, -2.5)));
+        assert_eq!("1e-3+2E+2j".parse::<C64>(), Ok(C64::new(0.001, 200.0)));
+        assert_eq!("4".parse::<C64>(), Ok(C64::new(4.0, 0.0)));
+        assert_eq!("-i".parse::<C64>(), Ok(C64::new(0.0, -1.0)));
+        assert_eq!("3i".parse::<C64>(), Ok(C64::new(0.0, 3.0)));
+        let z = C64::new(0.25, -8.0);
+        assert_eq!(z.to_string().parse::<C64>(), Ok(z));
+        assert!("1+2".parse::<C64>().is_err());
+        assert!("one".parse::<C64>().is_err());
+        assert_eq!(C64::from_str_radix("10+ai", 16), Ok(C64::new(16.0, 10.0)));
+    }
+
+    #[test]
+    fn test_num_trait() {
+        fn horner<N: Num + Copy>(coefficients: &[N], x: N) -> N {
+            coefficients.iter().fold(N::zero(), |value, coefficient| value * x + *coefficient)
+        }
+        // z² + 1 vanishes at ±i.
+        assert_eq!(horner(&[C64::one(), C64::zero(), C64::one()], C64::i()), C64::zero());
+        assert_eq!(C64::new(7.0, 3.0) % C64::new(2.0, 0.0), C64::new(1.0, 1.0));
+    }
+
+    #[test]
+    fn test_division_at_extreme_magnitudes() {
+        let huge = C64::new(1e300, 1e300);
+        assert_close(huge / huge, C64::one());
+        assert_close(C64::new(1e300, -1e300) / C64::new(1e300, 1e300), C64::new(0.0, -1.0));
+        let tiny = C64::new(1e-300, 3e-300);
+        assert_close(tiny / tiny, C64::one());
+        assert_close(C64::new(1.0, 1.0) / C64::new(1e-300, 0.0), C64::new(1e300, 1e300));
+        assert_close(C64::new(1.0, 2.0) / C64::new(3.0, 4.0), C64::new(0.44, 0.08));
+        assert_close(C64::new(1.0, 2.0) / C64::new(4.0, 3.0), C64::new(0.4, 0.2));
+        let mut quotient = C64::new(2e-310, 0.0);
+        quotient /= C64::new(0.0, 1e-310);
+        assert_close(quotient, C64::new(0.0, -2.0));
+    }
+
+    #[test]
+    fn test_magnitude_at_extreme_magnitudes() {
+        assert_eq!(C64::new(3e300, 4e300).magnitude(), 5e300);
+        assert!((C64::new(3e-310, 4e-310).magnitude() - 5e-310).abs() < 1e-320);
+        assert_eq!(C64::new(3.0, -4.0).norm_sqr(), 25.0);
+        assert_eq!(C64::new(1e200, 0.0).norm_sqr(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_sqrt() {
+        for z in grid() {
+            let root = z.sqrt();
+            assert_close(root * root, z);
+            assert!(root.real >= 0.0);
+        }
+        assert_eq!(C64::new(-4.0, 0.0).sqrt(), C64::new(0.0, 2.0));
+        assert_eq!(C64::new(-4.0, -0.0).sqrt(), C64::new(0.0, -2.0));
+        assert_eq!(C64::new(9.0, 0.0).sqrt(), C64::new(3.0, 0.0));
+        assert_eq!(C64::new(0.0, 2.0).sqrt(), C64::new(1.0, 1.0));
+        assert_eq!(C64::zero().sqrt(), C64::zero());
+        let huge = C64::new(f64::MAX, f64::MAX).sqrt();
+        assert!(huge.real.is_finite() && huge.imag.is_finite());
+        assert_close(huge / f64::MAX.sqrt(), C64::new(1.0, 1.0).sqrt());
+        let tiny = C64::new(-1e-310, 0.0).sqrt();
+        assert!((tiny.imag - 1e-155).abs() < 1e-165 && tiny.real == 0.0);
+    }
+
+    #[test]
+    fn test_sqrt_special_values() {
+        let inf = f64::INFINITY;
+        let nan = f64::NAN;
+        assert_eq!(C64::new(3.0, inf).sqrt(), C64::new(inf, inf));
+        assert_eq!(C64::new(-inf, -inf).sqrt(), C64::new(inf, -inf));
+        assert_eq!(C64::new(nan, inf).sqrt(), C64::new(inf, inf));
+        assert_eq!(C64::new(-inf, 2.0).sqrt(), C64::new(0.0, inf));
+        assert_eq!(C64::new(-inf, -2.0).sqrt(), C64::new(0.0, -inf));
+        let positive = C64::new(inf, -2.0).sqrt();
+        assert_eq!(positive, C64::new(inf, 0.0));
+        assert!(positive.imag.is_sign_negative());
+        let root = C64::new(-inf, nan).sqrt();
+        assert!(root.real.is_nan() && root.imag.is_infinite());
+        let root = C64::new(inf, nan).sqrt();
+        assert!(root.real == inf && root.imag.is_nan());
+        for z in [C64::new(nan, 1.0), C64::new(1.0, nan), C64::new(nan, nan)] {
+            let root = z.sqrt();
+            assert!(root.real.is_nan() && root.imag.is_nan(), "{:?}", root);
+        }
+    }
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(std::mem::size_of::<C64>(), 2 * std::mem::size_of::<f64>());
+        assert_eq!(std::mem::align_of::<C32>(), std::mem::align_of::<f32>());
+        assert_eq!(std::mem::offset_of!(C64, imag), std::mem::size_of::<f64>());
+    }
+
+    #[test]
+    fn test_interleaved_views() {
+        let mut samples = vec![1.0f32, 2.0, -3.0, 4.0, 0.5, -0.5];
+        let values = from_interleaved(&samples).unwrap();
+        assert_eq!(values, &[C32::new(1.0, 2.0), C32::new(-3.0, 4.0), C32::new(0.5, -0.5)]);
+        assert_eq!(as_interleaved(values), samples.as_slice());
+        from_interleaved_mut(&mut samples).unwrap()[1] = C32::i();
+        assert_eq!(samples[2..4], [0.0, 1.0]);
+        assert!(from_interleaved(&samples[1..]).is_none());
+    }
+
+    #[test]
+    fn test_fixed_point_iq() {
+        let samples: [i16; 5] = [i16::MIN, 16384, 0, -16384, 7];
+        assert_eq!(from_iq_i16(&samples, 1.0 / 32768.0), vec![C32::new(-1.0, 0.5), C32::new(0.0, -0.5)]);
+        let samples: [i8; 4] = [127, -128, 64, 0];
+        assert_eq!(from_iq_i8(&samples, 1.0 / 128.0), vec![C32::new(127.0 / 128.0, -1.0), C32::new(0.5, 0.0)]);
+    }
+
+    #[cfg(feature = "num-complex")]
+    #[test]
+    fn test_num_complex_interop() {
+        let z = C64::new(1.5, -2.0);
+        let other: num_complex::Complex<f64> = z.into();
+        assert_eq!(other, num_complex::Complex::new(1.5, -2.0));
+        assert_eq!(C64::from(other), z);
+
+        let mut values = vec![C32::new(1.0, 2.0), C32::new(-3.0, 4.0)];
+        assert_eq!(as_num_complex_slice(&values)[1], num_complex::Complex::new(-3.0f32, 4.0));
+        as_num_complex_slice_mut(&mut values)[0] *= 2.0;
+        assert_eq!(values[0], C32::new(2.0, 4.0));
+
+        let mut others = vec![num_complex::Complex::new(0.5f64, 0.25)];
+        assert_eq!(from_num_complex_slice(&others), &[C64::new(0.5, 0.25)]);
+        from_num_complex_slice_mut(&mut others)[0] = C64::i();
+        assert_eq!(others[0], num_complex::Complex::i());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let values = vec![C64::new(1.5, -2.0), C64::i()];
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, "[[1.5,-2.0],[0.0,1.0]]");
+        assert_eq!(serde_json::from_str::<Vec<C64>>(&json).unwrap(), values);
+        let bytes = bincode::serialize(&C32::new(0.25, 4.0)).unwrap();
+        assert_eq!(bytes.len(), 8);
+        assert_eq!(bincode::deserialize::<C32>(&bytes).unwrap(), C32::new(0.25, 4.0));
+        assert!(serde_json::from_str::<C64>("[1.0]").is_err());
+    }
+
+    #[test]
+    fn test_atan2() {
+        for (y, x) in [(1.0, 1.0), (1.0, -1.0), (-1.0, -1.0), (-2.0, 0.5), (0.0, -3.0), (0.0, 0.0)] {
+            assert_close(C64::atan2(C64::from(y), C64::from(x)), C64::from(f64::atan2(y, x)));
+        }
+        for z in grid() {
+            assert_close(C64::atan2(z, C64::one()), z.atan());
+        }
+    }
+
+    #[test]
+    fn test_atan2_singular() {
+        let i = C64::i();
+        assert_eq!(C64::atan2(i, C64::one()), C64::new(0.0, f64::INFINITY));
+        assert_eq!(C64::atan2(i, C64::one()), i.atan());
+        assert_eq!(C64::atan2(-i * 2.0, C64::new(2.0, 0.0)), C64::new(0.0, f64::NEG_INFINITY));
+        assert_eq!(C64::atan2(C64::one(), -i), C64::new(0.0, f64::INFINITY));
+        assert_eq!(C64::atan2(C64::zero(), C64::zero()), C64::zero());
     }
 }
\ No newline at end of file
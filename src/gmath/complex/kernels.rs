@@ -0,0 +1,403 @@
+use num_traits::Float;
+use crate::gmath::complex::Complex;
+
+/// Instruction set used by the slice kernels, detected at runtime.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SimdLevel {
+    Scalar,
+    Sse3,
+    Avx,
+}
+
+pub fn get_simd_level() -> SimdLevel {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx") {
+            return SimdLevel::Avx;
+        }
+        if is_x86_feature_detected!("sse3") {
+            return SimdLevel::Sse3;
+        }
+    }
+    SimdLevel::Scalar
+}
+
+/// Float types with vectorized complex slice kernels. The methods panic if
+/// the slice lengths differ, before any vectorized code runs.
+pub trait KernelFloat: Float + std::fmt::Display + std::fmt::Debug {
+    fn mul_slices(a: &[Complex<Self>], b: &[Complex<Self>], output: &mut [Complex<Self>]);
+    fn mul_conj_slices(a: &[Complex<Self>], b: &[Complex<Self>], output: &mut [Complex<Self>]);
+    fn norm_sqr_slice(values: &[Complex<Self>], output: &mut [Self]);
+    fn scale_slice(values: &[Complex<Self>], factor: Self, output: &mut [Complex<Self>]);
+}
+
+/// `output[k] = a[k] * b[k]`. Panics if the lengths differ.
+pub fn mul_elementwise<T: KernelFloat>(a: &[Complex<T>], b: &[Complex<T>], output: &mut [Complex<T>]) {
+    T::mul_slices(a, b, output);
+}
+
+/// `output[k] = a[k] * conj(b[k])`, e.g. for correlation and matched
+/// filtering. Panics if the lengths differ.
+pub fn mul_conj_elementwise<T: KernelFloat>(a: &[Complex<T>], b: &[Complex<T>], output: &mut [Complex<T>]) {
+    T::mul_conj_slices(a, b, output);
+}
+
+/// `output[k] = |values[k]|²`. Panics if the lengths differ.
+pub fn norm_sqr_elementwise<T: KernelFloat>(values: &[Complex<T>], output: &mut [T]) {
+    T::norm_sqr_slice(values, output);
+}
+
+/// `output[k] = values[k] * factor`. Panics if the lengths differ.
+pub fn scale_elementwise<T: KernelFloat>(values: &[Complex<T>], factor: T, output: &mut [Complex<T>]) {
+    T::scale_slice(values, factor, output);
+}
+
+fn check_lengths(first: usize, second: usize, output: usize) {
+    assert!(first == second && first == output,
+            "slice lengths differ: {}, {} and output {}", first, second, output);
+}
+
+/// Reference implementations, also used for the tail not filling a vector.
+mod scalar {
+    use super::*;
+
+    pub fn mul<T: KernelFloat>(a: &[Complex<T>], b: &[Complex<T>], output: &mut [Complex<T>]) {
+        for ((x, y), result) in a.iter().zip(b).zip(output.iter_mut()) {
+            *result = Complex { real: x.real * y.real - x.imag * y.imag, imag: x.imag * y.real + x.real * y.imag };
+        }
+    }
+
+    pub fn mul_conj<T: KernelFloat>(a: &[Complex<T>], b: &[Complex<T>], output: &mut [Complex<T>]) {
+        for ((x, y), result) in a.iter().zip(b).zip(output.iter_mut()) {
+            *result = Complex { real: x.real * y.real + x.imag * y.imag, imag: x.imag * y.real - x.real * y.imag };
+        }
+    }
+
+    pub fn norm_sqr<T: KernelFloat>(values: &[Complex<T>], output: &mut [T]) {
+        for (x, result) in values.iter().zip(output.iter_mut()) {
+            *result = x.real * x.real + x.imag * x.imag;
+        }
+    }
+
+    pub fn scale<T: KernelFloat>(values: &[Complex<T>], factor: T, output: &mut [Complex<T>]) {
+        for (x, result) in values.iter().zip(output.iter_mut()) {
+            *result = Complex { real: x.real * factor, imag: x.imag * factor };
+        }
+    }
+}
+
+/// Generate the kernels of one float type and instruction set. Each kernel
+/// processes the longest prefix filling whole vectors and returns its length.
+///
+/// `real_parts` duplicates the real part of each complex number over both
+/// of its slots, `imag_parts` the imaginary part, and `swap_parts` exchanges
+/// them, so `a * b` is `addsub(a * real_parts(b), swap_parts(a) * imag_parts(b))`.
+/// `pair_sums` adds the two slots of each complex number of a vector into a
+/// `$sums` vector whose first lanes `store_sums` writes out in order.
+#[cfg(target_arch = "x86_64")]
+macro_rules! x86_kernels {
+    ($module:ident, $float:ty, $feature:literal, $vector:ty, $sums:ty, $lanes:expr,
+     load: $load:ident, store: $store:ident, mul: $mul:ident, sub: $sub:ident,
+     addsub: $addsub:ident, zero: $zero:ident, splat: $splat:ident,
+     real_parts($real_arg:ident) => $real_parts:expr,
+     imag_parts($imag_arg:ident) => $imag_parts:expr,
+     swap_parts($swap_arg:ident) => $swap_parts:expr,
+     pair_sums($pair_arg:ident) => $pair_sums:expr,
+     store_sums($store_pointer:ident, $store_arg:ident) => $store_sums:expr) => {
+        mod $module {
+            use std::arch::x86_64::*;
+            use crate::gmath::complex::Complex;
+
+            /// Complex numbers per vector register.
+            const LANES: usize = $lanes;
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn real_parts($real_arg: $vector) -> $vector {
+                $real_parts
+            }
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn imag_parts($imag_arg: $vector) -> $vector {
+                $imag_parts
+            }
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn swap_parts($swap_arg: $vector) -> $vector {
+                $swap_parts
+            }
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn pair_sums($pair_arg: $vector) -> $sums {
+                $pair_sums
+            }
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn store_sums($store_pointer: *mut $float, $store_arg: $sums) {
+                $store_sums
+            }
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn load(values: &[Complex<$float>], index: usize) -> $vector {
+                $load(values.as_ptr().add(index) as *const $float)
+            }
+
+            #[inline]
+            #[target_feature(enable = $feature)]
+            unsafe fn store(values: &mut [Complex<$float>], index: usize, vector: $vector) {
+                $store(values.as_mut_ptr().add(index) as *mut $float, vector)
+            }
+
+            #[target_feature(enable = $feature)]
+            pub unsafe fn mul(a: &[Complex<$float>], b: &[Complex<$float>], output: &mut [Complex<$float>]) -> usize {
+                let end = a.len() / LANES * LANES;
+                for index in (0..end).step_by(LANES) {
+                    let (x, y) = (load(a, index), load(b, index));
+                    store(output, index, $addsub($mul(x, real_parts(y)), $mul(swap_parts(x), imag_parts(y))));
+                }
+                end
+            }
+
+            #[target_feature(enable = $feature)]
+            pub unsafe fn mul_conj(a: &[Complex<$float>], b: &[Complex<$float>], output: &mut [Complex<$float>]) -> usize {
+                let end = a.len() / LANES * LANES;
+                for index in (0..end).step_by(LANES) {
+                    let (x, y) = (load(a, index), load(b, index));
+                    let cross = $sub($zero(), $mul(swap_parts(x), imag_parts(y)));
+                    store(output, index, $addsub($mul(x, real_parts(y)), cross));
+                }
+                end
+            }
+
+            #[target_feature(enable = $feature)]
+            pub unsafe fn norm_sqr(values: &[Complex<$float>], output: &mut [$float]) -> usize {
+                let end = values.len() / LANES * LANES;
+                for index in (0..end).step_by(LANES) {
+                    let x = load(values, index);
+                    store_sums(output.as_mut_ptr().add(index), pair_sums($mul(x, x)));
+                }
+                end
+            }
+
+            #[target_feature(enable = $feature)]
+            pub unsafe fn scale(values: &[Complex<$float>], factor: $float, output: &mut [Complex<$float>]) -> usize {
+                let end = values.len() / LANES * LANES;
+                let factor = $splat(factor);
+                for index in (0..end).step_by(LANES) {
+                    store(output, index, $mul(load(values, index), factor));
+                }
+                end
+            }
+        }
+    };
+}
+
+#[cfg(target_arch = "x86_64")]
+x86_kernels!(avx_f32, f32, "avx", __m256, __m128, 4,
+    load: _mm256_loadu_ps, store: _mm256_storeu_ps, mul: _mm256_mul_ps, sub: _mm256_sub_ps, addsub: _mm256_addsub_ps, zero: _mm256_setzero_ps, splat: _mm256_set1_ps,
+    real_parts(v) => _mm256_moveldup_ps(v),
+    imag_parts(v) => _mm256_movehdup_ps(v),
+    swap_parts(v) => _mm256_permute_ps(v, 0b10_11_00_01),
+    pair_sums(v) => _mm_hadd_ps(_mm256_castps256_ps128(v), _mm256_extractf128_ps(v, 1)),
+    store_sums(pointer, sums) => _mm_storeu_ps(pointer, sums));
+
+#[cfg(target_arch = "x86_64")]
+x86_kernels!(sse3_f32, f32, "sse3", __m128, __m128, 2,
+    load: _mm_loadu_ps, store: _mm_storeu_ps, mul: _mm_mul_ps, sub: _mm_sub_ps, addsub: _mm_addsub_ps, zero: _mm_setzero_ps, splat: _mm_set1_ps,
+    real_parts(v) => _mm_moveldup_ps(v),
+    imag_parts(v) => _mm_movehdup_ps(v),
+    swap_parts(v) => _mm_shuffle_ps(v, v, 0b10_11_00_01),
+    pair_sums(v) => _mm_hadd_ps(v, v),
+    store_sums(pointer, sums) => _mm_store_sd(pointer as *mut f64, _mm_castps_pd(sums)));
+
+#[cfg(target_arch = "x86_64")]
+x86_kernels!(avx_f64, f64, "avx", __m256d, __m128d, 2,
+    load: _mm256_loadu_pd, store: _mm256_storeu_pd, mul: _mm256_mul_pd, sub: _mm256_sub_pd, addsub: _mm256_addsub_pd, zero: _mm256_setzero_pd, splat: _mm256_set1_pd,
+    real_parts(v) => _mm256_movedup_pd(v),
+    imag_parts(v) => _mm256_permute_pd(v, 0b1111),
+    swap_parts(v) => _mm256_permute_pd(v, 0b0101),
+    pair_sums(v) => _mm_hadd_pd(_mm256_castpd256_pd128(v), _mm256_extractf128_pd(v, 1)),
+    store_sums(pointer, sums) => _mm_storeu_pd(pointer, sums));
+
+#[cfg(target_arch = "x86_64")]
+x86_kernels!(sse3_f64, f64, "sse3", __m128d, __m128d, 1,
+    load: _mm_loadu_pd, store: _mm_storeu_pd, mul: _mm_mul_pd, sub: _mm_sub_pd, addsub: _mm_addsub_pd, zero: _mm_setzero_pd, splat: _mm_set1_pd,
+    real_parts(v) => _mm_movedup_pd(v),
+    imag_parts(v) => _mm_unpackhi_pd(v, v),
+    swap_parts(v) => _mm_shuffle_pd(v, v, 0b01),
+    pair_sums(v) => _mm_hadd_pd(v, v),
+    store_sums(pointer, sums) => _mm_store_sd(pointer, sums));
+
+macro_rules! impl_kernel_float {
+    ($float:ty, $avx:ident, $sse3:ident) => {
+        impl KernelFloat for $float {
+            fn mul_slices(a: &[Complex<$float>], b: &[Complex<$float>], output: &mut [Complex<$float>]) {
+                check_lengths(a.len(), b.len(), output.len());
+                let done = match get_simd_level() {
+                    // SAFETY: the instruction set was detected and the lengths checked just above.
+                    #[cfg(target_arch = "x86_64")]
+                    SimdLevel::Avx => unsafe { $avx::mul(a, b, output) },
+                    #[cfg(target_arch = "x86_64")]
+                    SimdLevel::Sse3 => unsafe { $sse3::mul(a, b, output) },
+                    _ => 0,
+                };
+                scalar::mul(&a[done..], &b[done..], &mut output[done..]);
+            }
+
+            fn mul_conj_slices(a: &[Complex<$float>], b: &[Complex<$float>], output: &mut [Complex<$float>]) {
+                check_lengths(a.len(), b.len(), output.len());
+                let done = match get_simd_level() {
+                    // SAFETY: see `mul_slices`.
+                    #[cfg(target_arch = "x86_64")]
+                    SimdLevel::Avx => unsafe { $avx::mul_conj(a, b, output) },
+                    #[cfg(target_arch = "x86_64")]
+                    SimdLevel::Sse3 => unsafe { $sse3::mul_conj(a, b, output) },
+                    _ => 0,
+                };
+                scalar::mul_conj(&a[done..], &b[done..], &mut output[done..]);
+            }
+
+            fn norm_sqr_slice(values: &[Complex<$float>], output: &mut [$float]) {
+                check_lengths(values.len(), values.len(), output.len());
+                let done = match get_simd_level() {
+                    // SAFETY: see `mul_slices`.
+                    #[cfg(target_arch = "x86_64")]
+                    SimdLevel::Avx => unsafe { $avx::norm_sqr(values, output) },
+                    #[cfg(target_arch = "x86_64")]
+                    SimdLevel::Sse3 => unsafe { $sse3::norm_sqr(values, output) },
+                    _ => 0,
+                };
+                scalar::norm_sqr(&values[done..], &mut output[done..]);
+            }
+
+            fn scale_slice(values: &[Complex<$float>], factor: $float, output: &mut [Complex<$float>]) {
+                check_lengths(values.len(), values.len(), output.len());
+                let done = match get_simd_level() {
+                    // SAFETY: see `mul_slices`.
+                    #[cfg(target_arch = "x86_64")]
+                    SimdLevel::Avx => unsafe { $avx::scale(values, factor, output) },
+                    #[cfg(target_arch = "x86_64")]
+                    SimdLevel::Sse3 => unsafe { $sse3::scale(values, factor, output) },
+                    _ => 0,
+                };
+                scalar::scale(&values[done..], factor, &mut output[done..]);
+            }
+        }
+    };
+}
+
+impl_kernel_float!(f32, avx_f32, sse3_f32);
+impl_kernel_float!(f64, avx_f64, sse3_f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gmath::complex::{C32, C64, ComplexTrait};
+
+    fn signal<T: KernelFloat>(length: usize, seed: f64) -> Vec<Complex<T>> {
+        (0..length)
+            .map(|k| {
+                let x = k as f64 + seed;
+                Complex::new(T::from((x * 0.37).sin() * 3.0).unwrap(), T::from((x * 1.3).cos() - 0.25).unwrap())
+            })
+            .collect()
+    }
+
+    /// Lengths around the vector widths so both the vector and the tail paths run.
+    const LENGTHS: [usize; 6] = [0, 1, 2, 3, 7, 33];
+
+    #[test]
+    fn test_kernels_match_operators_f32() {
+        for length in LENGTHS {
+            let (a, b) = (signal::<f32>(length, 0.0), signal::<f32>(length, 5.0));
+            let mut output = vec![C32::default(); length];
+            mul_elementwise(&a, &b, &mut output);
+            assert!(output.iter().zip(a.iter().zip(&b)).all(|(result, (x, y))| *result == *x * *y));
+            mul_conj_elementwise(&a, &b, &mut output);
+            assert!(output.iter().zip(a.iter().zip(&b)).all(|(result, (x, y))| *result == *x * y.conjugate()));
+            scale_elementwise(&a, 0.5, &mut output);
+            assert!(output.iter().zip(&a).all(|(result, x)| *result == *x * 0.5));
+            let mut norms = vec![0.0f32; length];
+            norm_sqr_elementwise(&a, &mut norms);
+            assert!(norms.iter().zip(&a).all(|(norm, x)| *norm == x.norm_sqr()));
+        }
+    }
+
+    #[test]
+    fn test_kernels_match_operators_f64() {
+        for length in LENGTHS {
+            let (a, b) = (signal::<f64>(length, 1.0), signal::<f64>(length, 2.0));
+            let mut output = vec![C64::default(); length];
+            mul_elementwise(&a, &b, &mut output);
+            assert!(output.iter().zip(a.iter().zip(&b)).all(|(result, (x, y))| *result == *x * *y));
+            mul_conj_elementwise(&a, &b, &mut output);
+            assert!(output.iter().zip(a.iter().zip(&b)).all(|(result, (x, y))| *result == *x * y.conjugate()));
+            scale_elementwise(&a, -3.0, &mut output);
+            assert!(output.iter().zip(&a).all(|(result, x)| *result == *x * -3.0));
+            let mut norms = vec![0.0f64; length];
+            norm_sqr_elementwise(&a, &mut norms);
+            assert!(norms.iter().zip(&a).all(|(norm, x)| *norm == x.norm_sqr()));
+        }
+    }
+
+    #[test]
+    fn test_scalar_fallback() {
+        let (a, b) = (signal::<f64>(5, 0.0), signal::<f64>(5, 1.0));
+        let mut output = vec![C64::default(); 5];
+        scalar::mul(&a, &b, &mut output);
+        assert!(output.iter().zip(a.iter().zip(&b)).all(|(result, (x, y))| *result == *x * *y));
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_sse3_kernels() {
+        if !is_x86_feature_detected!("sse3") {
+            return;
+        }
+        let (a, b) = (signal::<f32>(7, 0.0), signal::<f32>(7, 3.0));
+        let (mut output, mut expected) = (vec![C32::default(); 7], vec![C32::default(); 7]);
+        // SAFETY: SSE3 was detected and the lengths are equal.
+        assert_eq!(unsafe { sse3_f32::mul_conj(&a, &b, &mut output) }, 6);
+        scalar::mul_conj(&a, &b, &mut expected);
+        assert_eq!(output[..6], expected[..6]);
+
+        let a = signal::<f64>(3, 1.0);
+        let (mut norms, mut expected) = (vec![0.0; 3], vec![0.0; 3]);
+        assert_eq!(unsafe { sse3_f64::norm_sqr(&a, &mut norms) }, 3);
+        scalar::norm_sqr(&a, &mut expected);
+        assert_eq!(norms, expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "slice lengths differ")]
+    fn test_length_mismatch() {
+        let a = signal::<f32>(4, 0.0);
+        let mut output = vec![C32::default(); 3];
+        mul_elementwise(&a, &a, &mut output);
+    }
+
+    /// The trait methods are public too and must not reach the vectorized
+    /// code with a short output.
+    #[test]
+    fn test_trait_methods_check_lengths() {
+        let a = signal::<f64>(64, 0.0);
+        let mut short = vec![C64::default(); 3];
+        let mut short_norms = vec![0.0f64; 3];
+        let calls: [Box<dyn Fn(&mut Vec<C64>, &mut Vec<f64>)>; 4] = [
+            Box::new(|output, _| f64::mul_slices(&a, &a, output)),
+            Box::new(|output, _| f64::mul_conj_slices(&a, &a[..5], output)),
+            Box::new(|_, norms| f64::norm_sqr_slice(&a, norms)),
+            Box::new(|output, _| f64::scale_slice(&a, 2.0, output)),
+        ];
+        for call in calls {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| call(&mut short, &mut short_norms)));
+            assert!(result.is_err());
+        }
+        assert!(short.iter().all(|value| *value == C64::default()));
+    }
+}
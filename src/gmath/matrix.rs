@@ -1,91 +1,741 @@
-use num_traits::{Zero, One};
+use num_traits::{Float, Zero, One};
+use crate::gmath::vector::{Vector, VectorElement};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatrixError {
+    /// The operand has shape `found` (rows, columns) where `expected` was required.
+    DimensionMismatch { expected: (usize, usize), found: (usize, usize) },
+    /// A row or buffer has `found` elements where `expected` were required.
+    InvalidLength { expected: usize, found: usize },
+    NotSquare { rows: usize, cols: usize },
+}
+
+impl std::fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatrixError::DimensionMismatch { expected, found } =>
+                write!(f, "Dimension mismatch: expected {}x{}, found {}x{}", expected.0, expected.1, found.0, found.1),
+            MatrixError::InvalidLength { expected, found } =>
+                write!(f, "Invalid length: expected {} elements, found {}", expected, found),
+            MatrixError::NotSquare { rows, cols } => write!(f, "Matrix is not square: {}x{}", rows, cols),
+        }
+    }
+}
+
+impl std::error::Error for MatrixError {}
+
+/// Dense matrix stored as one contiguous row-major buffer, so rows are plain
+/// slices and the buffer can be handed to BLAS-style routines as is.
+///
+/// Deserialization goes through `Matrix::from_vec`, so a buffer that doesn't
+/// match the shape is rejected.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawMatrix<T>"))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "T: Copy + serde::Deserialize<'de>")))]
 pub struct Matrix<T> {
-    pub data: Vec<Vec<T>>,
-    pub shape: Option<(usize, usize)>,
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
 }
 
-impl <T: Clone + Zero + One + Copy + std::cmp::PartialEq> Matrix<T> {
+/// Unchecked serialized form of a `Matrix`.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawMatrix<T> {
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+#[cfg(feature = "serde")]
+impl<T: Copy> TryFrom<RawMatrix<T>> for Matrix<T> {
+    type Error = MatrixError;
+
+    fn try_from(raw: RawMatrix<T>) -> Result<Self, MatrixError> {
+        Matrix::from_vec(raw.rows, raw.cols, raw.data)
+    }
+}
+
+impl<T: Copy> Matrix<T> {
+    /// Build from a list of rows. Panics if the rows differ in length, see
+    /// `from_rows` for the checked variant.
     pub fn new(data: Vec<Vec<T>>) -> Self {
-        let shape = if data.is_empty() {
-            None
-        } else {
-            Some((data.len(), data[0].len()))
-        };
-        Matrix {
-            data,
-            shape,
+        match Matrix::from_rows(&data) {
+            Ok(matrix) => matrix,
+            Err(_) => panic!("Matrix rows must all have the same length"),
         }
     }
 
+    pub fn from_rows(rows: &[Vec<T>]) -> Result<Self, MatrixError> {
+        let cols = rows.first().map_or(0, |row| row.len());
+        let mut data = Vec::with_capacity(rows.len() * cols);
+        for row in rows {
+            if row.len() != cols {
+                return Err(MatrixError::InvalidLength { expected: cols, found: row.len() });
+            }
+            data.extend_from_slice(row);
+        }
+        Ok(Matrix { data, rows: rows.len(), cols })
+    }
+
+    /// Wrap a row-major buffer of `rows * cols` elements without copying it.
+    pub fn from_vec(rows: usize, cols: usize, data: Vec<T>) -> Result<Self, MatrixError> {
+        if data.len() != rows * cols {
+            return Err(MatrixError::InvalidLength { expected: rows * cols, found: data.len() });
+        }
+        Ok(Matrix { data, rows, cols })
+    }
+
     pub fn from_slice(slice: &[Vec<T>]) -> Self {
         Matrix::new(slice.to_vec())
     }
 
     pub fn to_vec(&self) -> Vec<Vec<T>> {
-        self.data.clone()
+        self.row_iter().map(|row| row.to_vec()).collect()
+    }
+
+    /// The row-major buffer.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
     }
 
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// (rows, columns)
     pub fn size(&self) -> (usize, usize) {
-        (self.data.len(), if !self.data.is_empty() { self.data[0].len() } else { 0 })
+        (self.rows, self.cols)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Distance in elements between consecutive rows and consecutive columns.
+    pub fn strides(&self) -> (usize, usize) {
+        (self.cols, 1)
     }
 
     pub fn is_square(&self) -> bool {
-        match self.shape {
-            Some((rows, cols)) => rows == cols,
-            None => false,
+        self.rows == self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.rows && col < self.cols {
+            self.data.get(row * self.cols + col)
+        } else {
+            None
         }
     }
 
+    pub fn row(&self, row: usize) -> &[T] {
+        assert!(row < self.rows, "Row index out of bounds");
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        assert!(row < self.rows, "Row index out of bounds");
+        &mut self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /// The rows as slices, `rows` empty ones for a matrix without columns.
+    pub fn row_iter(&self) -> impl ExactSizeIterator<Item = &[T]> + '_ {
+        (0..self.rows).map(move |row| self.row(row))
+    }
+
+    /// Strided view of a column.
+    pub fn column(&self, col: usize) -> std::iter::StepBy<std::slice::Iter<'_, T>> {
+        assert!(col < self.cols, "Column index out of bounds");
+        self.data[col..].iter().step_by(self.cols)
+    }
+
+    pub fn column_mut(&mut self, col: usize) -> std::iter::StepBy<std::slice::IterMut<'_, T>> {
+        assert!(col < self.cols, "Column index out of bounds");
+        let stride = self.cols;
+        self.data[col..].iter_mut().step_by(stride)
+    }
+
     pub fn zeros(rows: usize, cols: usize) -> Self
     where
-        T: num_traits::Zero + Clone,
+        T: Zero,
     {
-        let data = vec![vec![T::zero(); cols]; rows];
-        Matrix::new(data)
+        Matrix { data: vec![T::zero(); rows * cols], rows, cols }
     }
+
     pub fn ones(rows: usize, cols: usize) -> Self
     where
-        T: num_traits::One + Clone,
+        T: One,
     {
-        let data = vec![vec![T::one(); cols]; rows];
-        Matrix::new(data)
+        Matrix { data: vec![T::one(); rows * cols], rows, cols }
     }
 
     pub fn identity(size: usize) -> Self
     where
-        T: num_traits::One + num_traits::Zero + Clone,
+        T: Zero + One,
     {
-        let mut data = vec![vec![T::zero(); size]; size];
+        let mut identity = Matrix::zeros(size, size);
         for i in 0..size {
-            data[i][i] = T::one();
+            identity[(i, i)] = T::one();
         }
-        Matrix::new(data)
+        identity
     }
 
-    pub fn transpose(&mut self) -> Matrix<T> {
-        
-        let mut transposed: Vec<Vec<T>> = vec![vec![]; self.size().1];
-        for row in &self.data {
-            for (i, value) in row.iter().enumerate() {
-                transposed[i].push(value.clone());
+    /// Transpose in place by following the permutation cycles of the buffer.
+    /// Instead of a second matrix only one visited flag per element is
+    /// allocated.
+    pub fn transpose(&mut self) {
+        let length = self.data.len();
+        let mut visited = vec![false; length];
+        for start in 0..length {
+            if visited[start] {
+                continue;
+            }
+            let mut current = start;
+            let mut value = self.data[start];
+            loop {
+                let next = (current % self.cols) * self.rows + current / self.cols;
+                std::mem::swap(&mut self.data[next], &mut value);
+                visited[next] = true;
+                current = next;
+                if current == start {
+                    break;
+                }
             }
         }
-        Matrix::new(transposed)      
+        std::mem::swap(&mut self.rows, &mut self.cols);
     }
 
-    pub fn p_l_u_factorization(&self) -> (Vec<Vec<T>>,Vec<Vec<T>>) {
-        unimplemented!()
+    pub fn transposed(&self) -> Matrix<T> {
+        let mut data = Vec::with_capacity(self.data.len());
+        for col in 0..self.cols {
+            data.extend(self.column(col));
+        }
+        Matrix { data, rows: self.cols, cols: self.rows }
     }
-    pub fn determinant(&self) -> T
+
+    /// Element-wise product. Panics if the shapes differ.
+    pub fn hadamard(&self, other: &Matrix<T>) -> Matrix<T>
     where
-        T: std::ops::Mul<Output = T> + std::ops::Add<Output = T> + Copy + num_traits::Zero,
+        T: std::ops::Mul<Output = T>,
     {
+        match self.checked_hadamard(other) {
+            Ok(product) => product,
+            Err(_) => panic!("Matrices must have the same shape for the Hadamard product"),
+        }
+    }
+}
+
+/// Factors of `Matrix::p_l_u_factorization`, `p * A = l * u`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluFactorization<T> {
+    pub p: Matrix<T>,
+    /// Lower triangular with a unit diagonal.
+    pub l: Matrix<T>,
+    pub u: Matrix<T>,
+    /// A pivot of `u` is at most the tolerance times the largest element
+    /// magnitude of A, so A can't be inverted reliably.
+    pub singular: bool,
+}
+
+/// In-place LU factorization with partial pivoting: the strictly lower part
+/// of `lu` holds L (unit diagonal), the upper part U.
+struct LuDecomposition<T> {
+    lu: Matrix<T>,
+    /// Row of the original matrix moved to each row.
+    permutation: Vec<usize>,
+    swaps: usize,
+    singular: bool,
+}
+
+impl<T: VectorElement> Matrix<T> {
+    /// Pivots with a magnitude of at most `tolerance` times the largest
+    /// element magnitude mark the matrix singular. A zero pivot column has
+    /// nothing to eliminate and is skipped. NaN elements propagate.
+    fn lu_decompose(&self, tolerance: T::Real) -> Result<LuDecomposition<T>, MatrixError> {
         if !self.is_square() {
-            panic!("Determinant can only be calculated for square matrices");
+            return Err(MatrixError::NotSquare { rows: self.rows, cols: self.cols });
+        }
+        let size = self.rows;
+        let threshold = tolerance * self.data.iter().fold(T::Real::zero(), |max, value| max.max(value.magnitude()));
+        let mut lu = self.clone();
+        let mut permutation: Vec<usize> = (0..size).collect();
+        let mut swaps = 0;
+        let mut singular = false;
+        for k in 0..size {
+            let mut pivot_row = k;
+            for row in k + 1..size {
+                if lu[(row, k)].magnitude() > lu[(pivot_row, k)].magnitude() {
+                    pivot_row = row;
+                }
+            }
+            let pivot_magnitude = lu[(pivot_row, k)].magnitude();
+            if pivot_magnitude <= threshold {
+                singular = true;
+            }
+            if pivot_magnitude.is_zero() {
+                continue;
+            }
+            if pivot_row != k {
+                for col in 0..size {
+                    lu.data.swap(k * size + col, pivot_row * size + col);
+                }
+                permutation.swap(k, pivot_row);
+                swaps += 1;
+            }
+            let pivot = lu[(k, k)];
+            for row in k + 1..size {
+                let factor = lu[(row, k)] / pivot;
+                lu[(row, k)] = factor;
+                for col in k + 1..size {
+                    lu[(row, col)] = lu[(row, col)] - factor * lu[(k, col)];
+                }
+            }
+        }
+        Ok(LuDecomposition { lu, permutation, swaps, singular })
+    }
+
+    /// Factor the matrix as `P * self = L * U` with partial pivoting. The
+    /// factorization exists for singular matrices too. They are flagged by
+    /// `PluFactorization::singular` when a pivot magnitude is at most
+    /// `tolerance` times the largest element magnitude.
+    pub fn p_l_u_factorization(&self, tolerance: T::Real) -> Result<PluFactorization<T>, MatrixError> {
+        let decomposition = self.lu_decompose(tolerance)?;
+        let size = self.rows;
+        let mut p = Matrix::zeros(size, size);
+        let mut l = Matrix::identity(size);
+        let mut u = Matrix::zeros(size, size);
+        for row in 0..size {
+            p[(row, decomposition.permutation[row])] = T::one();
+            for col in 0..size {
+                let value = decomposition.lu[(row, col)];
+                if col < row {
+                    l[(row, col)] = value;
+                } else {
+                    u[(row, col)] = value;
+                }
+            }
+        }
+        Ok(PluFactorization { p, l, u, singular: decomposition.singular })
+    }
+
+    /// Determinant from the LU factorization: the product of the pivots, so
+    /// 0 for an exactly singular matrix and NaN if the matrix holds NaN.
+    pub fn determinant(&self) -> Result<T, MatrixError> {
+        let decomposition = self.lu_decompose(T::Real::zero())?;
+        let determinant = (0..self.rows).fold(T::one(), |product, i| product * decomposition.lu[(i, i)]);
+        Ok(if decomposition.swaps % 2 == 0 { determinant } else { -determinant })
+    }
+}
+
+/// Non panicking variants of the operators, for data that may be malformed.
+impl<T: Copy> Matrix<T> {
+    fn check_same_shape(&self, other: &Matrix<T>) -> Result<(), MatrixError> {
+        if self.size() != other.size() {
+            return Err(MatrixError::DimensionMismatch { expected: self.size(), found: other.size() });
+        }
+        Ok(())
+    }
+
+    fn checked_zip<F: Fn(T, T) -> T>(&self, other: &Matrix<T>, operation: F) -> Result<Matrix<T>, MatrixError> {
+        self.check_same_shape(other)?;
+        let data = self.data.iter().zip(&other.data).map(|(a, b)| operation(*a, *b)).collect();
+        Ok(Matrix { data, rows: self.rows, cols: self.cols })
+    }
+
+    pub fn checked_add(&self, other: &Matrix<T>) -> Result<Matrix<T>, MatrixError>
+    where
+        T: std::ops::Add<Output = T>,
+    {
+        self.checked_zip(other, |a, b| a + b)
+    }
+
+    pub fn checked_sub(&self, other: &Matrix<T>) -> Result<Matrix<T>, MatrixError>
+    where
+        T: std::ops::Sub<Output = T>,
+    {
+        self.checked_zip(other, |a, b| a - b)
+    }
+
+    pub fn checked_hadamard(&self, other: &Matrix<T>) -> Result<Matrix<T>, MatrixError>
+    where
+        T: std::ops::Mul<Output = T>,
+    {
+        self.checked_zip(other, |a, b| a * b)
+    }
+
+    /// Matrix product, `other` must have as many rows as `self` has columns.
+    pub fn checked_mul(&self, other: &Matrix<T>) -> Result<Matrix<T>, MatrixError>
+    where
+        T: Zero + std::ops::Mul<Output = T>,
+    {
+        if self.cols != other.rows {
+            return Err(MatrixError::DimensionMismatch { expected: (self.cols, other.cols), found: other.size() });
         }
-        // Implement determinant calculation here (e.g., using recursion or LU decomposition)
-        unimplemented!()
+        let mut product = Matrix::zeros(self.rows, other.cols);
+        // i-k-j order so the inner loop runs over contiguous rows.
+        for (row_index, row) in self.row_iter().enumerate() {
+            let product_row = product.row_mut(row_index);
+            for (value, other_row) in row.iter().zip(other.row_iter()) {
+                for (sum, other_value) in product_row.iter_mut().zip(other_row) {
+                    *sum = *sum + *value * *other_value;
+                }
+            }
+        }
+        Ok(product)
     }
-}
\ No newline at end of file
+
+    pub fn checked_mul_vector(&self, vector: &Vector<T>) -> Result<Vector<T>, MatrixError>
+    where
+        T: Zero + std::ops::Mul<Output = T>,
+    {
+        if self.cols != vector.len() {
+            return Err(MatrixError::DimensionMismatch { expected: (self.cols, 1), found: (vector.len(), 1) });
+        }
+        Ok((0..self.rows)
+            .map(|row| self.row(row).iter().zip(vector.iter()).fold(T::zero(), |sum, (a, b)| sum + *a * *b))
+            .collect())
+    }
+}
+
+impl<T> std::ops::Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(row < self.rows && col < self.cols, "Matrix index out of bounds");
+        &self.data[row * self.cols + col]
+    }
+}
+
+impl<T> std::ops::IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(row < self.rows && col < self.cols, "Matrix index out of bounds");
+        &mut self.data[row * self.cols + col]
+    }
+}
+
+/// Element-wise matrix operators. They panic if the shapes differ, the
+/// `checked_*` methods return an error instead.
+macro_rules! impl_elementwise_ops {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $operation:literal) => {
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$assign_trait<&Matrix<T>> for Matrix<T> {
+            fn $assign_method(&mut self, other: &Matrix<T>) {
+                if self.size() != other.size() {
+                    panic!(concat!("Matrices must have the same shape for ", $operation));
+                }
+                for (value, other) in self.data.iter_mut().zip(&other.data) {
+                    *value = std::ops::$trait::$method(*value, *other);
+                }
+            }
+        }
+
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$assign_trait for Matrix<T> {
+            fn $assign_method(&mut self, other: Matrix<T>) {
+                std::ops::$assign_trait::$assign_method(self, &other);
+            }
+        }
+
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$trait for Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(mut self, other: Matrix<T>) -> Matrix<T> {
+                std::ops::$assign_trait::$assign_method(&mut self, &other);
+                self
+            }
+        }
+
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$trait<&Matrix<T>> for Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(mut self, other: &Matrix<T>) -> Matrix<T> {
+                std::ops::$assign_trait::$assign_method(&mut self, other);
+                self
+            }
+        }
+
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$trait<&Matrix<T>> for &Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(self, other: &Matrix<T>) -> Matrix<T> {
+                self.clone().$method(other)
+            }
+        }
+    };
+}
+
+impl_elementwise_ops!(Add, add, AddAssign, add_assign, "addition");
+impl_elementwise_ops!(Sub, sub, SubAssign, sub_assign, "subtraction");
+
+macro_rules! impl_scalar_ops {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident) => {
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$assign_trait<T> for Matrix<T> {
+            fn $assign_method(&mut self, scalar: T) {
+                for value in self.data.iter_mut() {
+                    *value = std::ops::$trait::$method(*value, scalar);
+                }
+            }
+        }
+
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$trait<T> for Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(mut self, scalar: T) -> Matrix<T> {
+                std::ops::$assign_trait::$assign_method(&mut self, scalar);
+                self
+            }
+        }
+
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$trait<T> for &Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(self, scalar: T) -> Matrix<T> {
+                self.clone().$method(scalar)
+            }
+        }
+    };
+}
+
+impl_scalar_ops!(Add, add, AddAssign, add_assign);
+impl_scalar_ops!(Sub, sub, SubAssign, sub_assign);
+impl_scalar_ops!(Mul, mul, MulAssign, mul_assign);
+impl_scalar_ops!(Div, div, DivAssign, div_assign);
+
+/// Matrix product. Panics if the inner dimensions differ, see `checked_mul`.
+impl<T: Copy + Zero + std::ops::Mul<Output = T>> std::ops::Mul<&Matrix<T>> for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, other: &Matrix<T>) -> Matrix<T> {
+        match self.checked_mul(other) {
+            Ok(product) => product,
+            Err(_) => panic!("Matrix columns must match the other matrix rows for multiplication"),
+        }
+    }
+}
+
+impl<T: Copy + Zero + std::ops::Mul<Output = T>> std::ops::Mul for Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, other: Matrix<T>) -> Matrix<T> {
+        &self * &other
+    }
+}
+
+impl<T: Copy + Zero + std::ops::Mul<Output = T>> std::ops::Mul<&Vector<T>> for &Matrix<T> {
+    type Output = Vector<T>;
+
+    fn mul(self, vector: &Vector<T>) -> Vector<T> {
+        match self.checked_mul_vector(vector) {
+            Ok(product) => product,
+            Err(_) => panic!("Matrix columns must match the vector length for multiplication"),
+        }
+    }
+}
+
+impl<T: Copy + Zero + std::ops::Mul<Output = T>> std::ops::Mul<Vector<T>> for Matrix<T> {
+    type Output = Vector<T>;
+
+    fn mul(self, vector: Vector<T>) -> Vector<T> {
+        &self * &vector
+    }
+}
+
+/// `scalar * Matrix` for the primitive float types, which the orphan rule
+/// doesn't allow generically.
+macro_rules! impl_scalar_lhs_mul {
+    ($($scalar:ty),*) => {$(
+        impl std::ops::Mul<Matrix<$scalar>> for $scalar {
+            type Output = Matrix<$scalar>;
+
+            fn mul(self, matrix: Matrix<$scalar>) -> Matrix<$scalar> {
+                matrix * self
+            }
+        }
+    )*};
+}
+
+impl_scalar_lhs_mul!(f32, f64);
+
+impl<T: Copy + std::ops::Neg<Output = T>> std::ops::Neg for Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn neg(mut self) -> Matrix<T> {
+        for value in self.data.iter_mut() {
+            *value = -*value;
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gmath::complex::{C64, ComplexTrait};
+
+    fn sequence(rows: usize, cols: usize) -> Matrix<f64> {
+        Matrix::from_vec(rows, cols, (0..rows * cols).map(|value| value as f64).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_layout_and_views() {
+        let mut matrix = Matrix::new(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        assert_eq!(matrix.size(), (2, 3));
+        assert_eq!(matrix.strides(), (3, 1));
+        assert_eq!(matrix.as_slice(), &[1, 2, 3, 4, 5, 6]);
+        assert_eq!(matrix[(1, 2)], 6);
+        assert_eq!(matrix.get(2, 0), None);
+        assert_eq!(matrix.row(1), &[4, 5, 6]);
+        assert_eq!(matrix.column(1).copied().collect::<Vec<_>>(), vec![2, 5]);
+        for value in matrix.column_mut(0) {
+            *value *= 10;
+        }
+        matrix.row_mut(0)[2] = 0;
+        assert_eq!(matrix.to_vec(), vec![vec![10, 2, 0], vec![40, 5, 6]]);
+
+        assert_eq!(Matrix::from_rows(&[vec![1, 2], vec![3]]), Err(MatrixError::InvalidLength { expected: 2, found: 1 }));
+        assert_eq!(Matrix::from_vec(2, 2, vec![1, 2, 3]), Err(MatrixError::InvalidLength { expected: 4, found: 3 }));
+    }
+
+    #[test]
+    fn test_transpose() {
+        for (rows, cols) in [(1, 1), (3, 3), (2, 5), (4, 3), (1, 4)] {
+            let matrix = sequence(rows, cols);
+            let mut transposed = matrix.clone();
+            transposed.transpose();
+            assert_eq!(transposed.size(), (cols, rows));
+            assert_eq!(transposed, matrix.transposed());
+            for row in 0..rows {
+                for col in 0..cols {
+                    assert_eq!(transposed[(col, row)], matrix[(row, col)]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_dimensions() {
+        let mut no_columns = Matrix::<f64>::from_vec(3, 0, Vec::new()).unwrap();
+        assert_eq!(no_columns.to_vec(), vec![Vec::<f64>::new(); 3]);
+        assert_eq!(no_columns.row_iter().len(), 3);
+        assert_eq!(&no_columns * &Matrix::zeros(0, 2), Matrix::zeros(3, 2));
+        no_columns.transpose();
+        assert_eq!(no_columns.size(), (0, 3));
+        assert!(no_columns.to_vec().is_empty());
+    }
+
+    #[test]
+    fn test_operators() {
+        let a = Matrix::new(vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+        let b = Matrix::new(vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+        assert_eq!(&a + &b, Matrix::new(vec![vec![1.0, 3.0], vec![4.0, 4.0]]));
+        assert_eq!(&a - &b, Matrix::new(vec![vec![1.0, 1.0], vec![2.0, 4.0]]));
+        assert_eq!(&a * &b, Matrix::new(vec![vec![2.0, 1.0], vec![4.0, 3.0]]));
+        assert_eq!(a.hadamard(&b), Matrix::new(vec![vec![0.0, 2.0], vec![3.0, 0.0]]));
+        assert_eq!(2.0 * a.clone() - 1.0, Matrix::new(vec![vec![1.0, 3.0], vec![5.0, 7.0]]));
+        assert_eq!(-(&a / 2.0), Matrix::new(vec![vec![-0.5, -1.0], vec![-1.5, -2.0]]));
+        assert_eq!(&a * &Matrix::identity(2), a);
+        assert_eq!(a.clone() * Vector::new(vec![1.0, -1.0]), Vector::new(vec![-1.0, -1.0]));
+
+        let c = sequence(2, 3);
+        assert_eq!((&a * &c).size(), (2, 3));
+        assert_eq!(&c * &Vector::new(vec![1.0, 1.0, 1.0]), Vector::new(vec![3.0, 12.0]));
+    }
+
+    #[test]
+    fn test_dimension_errors() {
+        let a = sequence(2, 3);
+        let b = sequence(3, 2);
+        let mismatch = MatrixError::DimensionMismatch { expected: (2, 3), found: (3, 2) };
+        assert_eq!(a.checked_add(&b), Err(mismatch.clone()));
+        assert_eq!(a.checked_sub(&b), Err(mismatch.clone()));
+        assert_eq!(a.checked_hadamard(&b), Err(mismatch));
+        assert_eq!(a.checked_mul(&a), Err(MatrixError::DimensionMismatch { expected: (3, 3), found: (2, 3) }));
+        assert_eq!(a.checked_mul(&b).unwrap().size(), (2, 2));
+        assert_eq!(a.checked_mul_vector(&Vector::new(vec![1.0, 2.0])),
+                   Err(MatrixError::DimensionMismatch { expected: (3, 1), found: (2, 1) }));
+        assert_eq!(a.checked_add(&b).unwrap_err().to_string(), "Dimension mismatch: expected 2x3, found 3x2");
+    }
+
+    #[test]
+    #[should_panic(expected = "Matrices must have the same shape for addition")]
+    fn test_add_shape_mismatch() {
+        let _ = sequence(2, 3) + sequence(3, 2);
+    }
+
+    fn assert_matrix_close<T: VectorElement + std::fmt::Debug>(actual: &Matrix<T>, expected: &Matrix<T>)
+    where
+        T::Real: Into<f64>,
+    {
+        assert_eq!(actual.size(), expected.size());
+        for (a, b) in actual.as_slice().iter().zip(expected.as_slice()) {
+            assert!((*a - *b).magnitude().into() < 1e-12, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_p_l_u_factorization() {
+        let a = Matrix::new(vec![vec![2.0, 1.0, 1.0], vec![4.0, -6.0, 0.0], vec![-2.0, 7.0, 2.0]]);
+        let PluFactorization { p, l, u, singular } = a.p_l_u_factorization(1e-12).unwrap();
+        assert!(!singular);
+        assert_matrix_close(&(&p * &a), &(&l * &u));
+        assert_eq!(p.row(0), &[0.0, 1.0, 0.0]);
+        for i in 0..3 {
+            assert_eq!(l[(i, i)], 1.0);
+            for j in 0..i {
+                assert_eq!(u[(i, j)], 0.0);
+                assert!(l[(i, j)].abs() <= 1.0);
+            }
+        }
+        assert!((a.determinant().unwrap() - -16.0).abs() < 1e-12);
+        assert_eq!(Matrix::new(vec![vec![0.0, 1.0], vec![1.0, 0.0]]).determinant(), Ok(-1.0));
+        assert_eq!(Matrix::<f32>::identity(0).determinant(), Ok(1.0));
+
+        let z = Matrix::new(vec![vec![C64::new(1.0, 1.0), C64::i()], vec![C64::new(2.0, 0.0), C64::new(0.0, -1.0)]]);
+        let PluFactorization { p, l, u, .. } = z.p_l_u_factorization(1e-12).unwrap();
+        assert_matrix_close(&(&p * &z), &(&l * &u));
+        assert!((z.determinant().unwrap() - C64::new(1.0, -3.0)).magnitude() < 1e-12);
+    }
+
+    #[test]
+    fn test_singular_matrices() {
+        let singular = Matrix::new(vec![vec![1.0, 2.0, 3.0], vec![2.0, 4.0, 6.0], vec![1.0, 0.0, 1.0]]);
+        let factorization = singular.p_l_u_factorization(1e-12).unwrap();
+        assert!(factorization.singular);
+        assert_matrix_close(&(&factorization.p * &singular), &(&factorization.l * &factorization.u));
+        assert_eq!(singular.determinant(), Ok(0.0));
+
+        let nearly_singular = Matrix::new(vec![vec![1.0, 1.0], vec![1.0, 1.0 + 1e-10]]);
+        let factorization = nearly_singular.p_l_u_factorization(1e-8).unwrap();
+        assert!(factorization.singular);
+        assert_eq!(&factorization.l * &factorization.u, nearly_singular);
+        assert!(!nearly_singular.p_l_u_factorization(1e-12).unwrap().singular);
+
+        let zeros = Matrix::<f64>::zeros(2, 2);
+        assert!(zeros.p_l_u_factorization(0.0).unwrap().singular);
+        assert_eq!(zeros.determinant(), Ok(0.0));
+        let with_nan = Matrix::new(vec![vec![f64::NAN, 1.0], vec![2.0, 3.0]]);
+        assert!(with_nan.determinant().unwrap().is_nan());
+        assert!(Matrix::new(vec![vec![1.0, f64::NAN], vec![2.0, 3.0]]).determinant().unwrap().is_nan());
+        assert_eq!(sequence(2, 3).determinant(), Err(MatrixError::NotSquare { rows: 2, cols: 3 }));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let matrix = Matrix::new(vec![vec![C64::new(1.0, 0.5), C64::zero()], vec![C64::i(), C64::one()]]);
+        let json = serde_json::to_string(&matrix).unwrap();
+        assert_eq!(json, r#"{"data":[[1.0,0.5],[0.0,0.0],[0.0,1.0],[1.0,0.0]],"rows":2,"cols":2}"#);
+        let decoded: Matrix<C64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, matrix);
+        let error = serde_json::from_str::<Matrix<f64>>(r#"{"data":[1.0],"rows":5,"cols":5}"#).unwrap_err();
+        assert!(error.to_string().contains("Invalid length: expected 25 elements, found 1"));
+    }
+}
@@ -1,15 +1,120 @@
-use num_traits::Zero;
+use num_traits::{Float, One, Zero};
+use crate::gmath::complex::{Complex, ComplexTrait};
 
+/// Element types of a `Vector` with a magnitude, real or complex, so the
+/// norms and geometric operations work for both.
+pub trait VectorElement: Copy + Zero + One + std::ops::Neg<Output = Self>
+    + std::ops::Sub<Output = Self> + std::ops::Div<Output = Self> {
+    type Real: Float;
+
+    fn from_real(value: Self::Real) -> Self;
+    fn real_part(self) -> Self::Real;
+    fn magnitude(self) -> Self::Real;
+    fn conjugate(self) -> Self;
+    /// Ordering used by `argmin`/`argmax`: the value for real elements, the
+    /// magnitude for complex ones.
+    fn order_key(self) -> Self::Real;
+    fn sqrt(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+}
+
+macro_rules! impl_real_vector_element {
+    ($($float:ty),*) => {$(
+        impl VectorElement for $float {
+            type Real = $float;
+
+            fn from_real(value: $float) -> Self {
+                value
+            }
+            fn real_part(self) -> $float {
+                self
+            }
+            fn magnitude(self) -> $float {
+                self.abs()
+            }
+            fn conjugate(self) -> Self {
+                self
+            }
+            fn order_key(self) -> $float {
+                self
+            }
+            fn sqrt(self) -> Self {
+                <$float>::sqrt(self)
+            }
+            fn exp(self) -> Self {
+                <$float>::exp(self)
+            }
+            fn ln(self) -> Self {
+                <$float>::ln(self)
+            }
+        }
+    )*};
+}
+
+impl_real_vector_element!(f32, f64);
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> VectorElement for Complex<T> {
+    type Real = T;
+
+    fn from_real(value: T) -> Self {
+        Complex::from(value)
+    }
+    fn real_part(self) -> T {
+        self.real
+    }
+    fn magnitude(self) -> T {
+        ComplexTrait::magnitude(&self)
+    }
+    fn conjugate(self) -> Self {
+        ComplexTrait::conjugate(&self)
+    }
+    fn order_key(self) -> T {
+        ComplexTrait::magnitude(&self)
+    }
+    fn sqrt(self) -> Self {
+        Complex::sqrt(&self)
+    }
+    fn exp(self) -> Self {
+        Complex::exp(&self)
+    }
+    fn ln(self) -> Self {
+        Complex::ln(&self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VectorError {
+    /// The operand has `found` elements where `expected` were required.
+    DimensionMismatch { expected: usize, found: usize },
+    DivisionByZero,
+    /// A decimation or upsampling factor of 0.
+    ZeroFactor,
+}
+
+impl std::fmt::Display for VectorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VectorError::DimensionMismatch { expected, found } =>
+                write!(f, "Dimension mismatch: expected {} elements, found {}", expected, found),
+            VectorError::DivisionByZero => write!(f, "Division by zero"),
+            VectorError::ZeroFactor => write!(f, "Resampling factor must be positive"),
+        }
+    }
+}
+
+impl std::error::Error for VectorError {}
+
+/// Serialized as a plain sequence of its elements.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Vector<T>{
     pub data: Vec<T>,
 }
 
-impl <T: Clone + Copy 
-    + std::ops::Mul<Output=T> 
-    + std::ops::Div<Output=T> 
-    + num_traits::Zero 
-    + std::cmp::PartialEq> Vector<T> {
-    
+impl <T: Clone> Vector<T> {
+
     pub fn new(data: Vec<T>) -> Self {
         Vector { data }
     }
@@ -26,6 +131,82 @@ impl <T: Clone + Copy
         &self.data
     }
 
+    /// Take the elements back without copying them.
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Borrow a sub-range, `None` if it is out of bounds.
+    pub fn slice<R: std::ops::RangeBounds<usize>>(&self, range: R) -> Option<&[T]> {
+        self.data.get((range.start_bound().cloned(), range.end_bound().cloned()))
+    }
+
+    /// New vector holding `parts` one after the other.
+    pub fn concatenate(parts: &[&[T]]) -> Vector<T> {
+        Vector { data: parts.concat() }
+    }
+
+    pub fn append(&mut self, other: &[T]) {
+        self.data.extend_from_slice(other);
+    }
+
+    /// Extend with zeros up to `length`, e.g. the next FFT size. Never truncates.
+    pub fn zero_pad(&mut self, length: usize)
+    where
+        T: Zero,
+    {
+        if length > self.data.len() {
+            self.data.resize(length, T::zero());
+        }
+    }
+
+    /// Rotate the elements by `shift` positions, to the right when positive,
+    /// like `numpy.roll`.
+    pub fn circular_shift(&mut self, shift: isize) {
+        if self.data.is_empty() {
+            return;
+        }
+        let shift = shift.rem_euclid(self.data.len() as isize) as usize;
+        self.data.rotate_right(shift);
+    }
+
+    /// Move the zero frequency bin of an FFT output to the center.
+    pub fn fftshift(&mut self) {
+        let length = self.data.len();
+        self.data.rotate_right(length / 2);
+    }
+
+    /// Inverse of `fftshift`, also for odd lengths.
+    pub fn ifftshift(&mut self) {
+        let length = self.data.len();
+        self.data.rotate_left(length / 2);
+    }
+
+    /// Keep every `factor`-th element starting with the first. No
+    /// anti-aliasing filter is applied.
+    pub fn decimate(&self, factor: usize) -> Result<Vector<T>, VectorError> {
+        if factor == 0 {
+            return Err(VectorError::ZeroFactor);
+        }
+        Ok(self.data.iter().step_by(factor).cloned().collect())
+    }
+
+    /// Insert `factor - 1` zeros after each element.
+    pub fn upsample(&self, factor: usize) -> Result<Vector<T>, VectorError>
+    where
+        T: Zero,
+    {
+        if factor == 0 {
+            return Err(VectorError::ZeroFactor);
+        }
+        let mut data = Vec::with_capacity(self.data.len() * factor);
+        for value in &self.data {
+            data.push(value.clone());
+            data.extend(std::iter::repeat_n(T::zero(), factor - 1));
+        }
+        Ok(Vector { data })
+    }
+
     pub fn size(&self) -> usize {
         self.data.len()
     }
@@ -49,63 +230,673 @@ impl <T: Clone + Copy
         if self.data.len() != other.data.len() {
             panic!("Vectors must be of the same length for dot product");
         }
-        
+
         let mut result = T::zero();
         for (a, b) in self.data.iter().zip(&other.data) {
             result = result + (*a * *b);
         }
         result
     }
+}
 
-    fn mul(self, scalar: T) -> Self {
-        let mut result = self.data.clone();
-        for value in &mut result {
-            *value = *value * scalar;
+impl<T: VectorElement> Vector<T> {
+    /// Sum of the magnitudes.
+    pub fn norm_l1(&self) -> T::Real {
+        self.data.iter().fold(T::Real::zero(), |sum, value| sum + value.magnitude())
+    }
+
+    /// Euclidean norm, accumulated with `hypot` so it doesn't overflow.
+    pub fn norm_l2(&self) -> T::Real {
+        self.data.iter().fold(T::Real::zero(), |norm, value| norm.hypot(value.magnitude()))
+    }
+
+    /// Largest magnitude, 0 for an empty vector.
+    pub fn norm_inf(&self) -> T::Real {
+        self.data.iter().fold(T::Real::zero(), |norm, value| norm.max(value.magnitude()))
+    }
+
+    /// `(Σ |x|^p)^(1/p)` for `p >= 1`, infinite `p` gives `norm_inf`.
+    pub fn norm_p(&self, p: T::Real) -> T::Real {
+        if p.is_infinite() {
+            return self.norm_inf();
+        }
+        if p.is_one() {
+            return self.norm_l1();
         }
-        Vector { data: result }
+        self.data.iter()
+            .fold(T::Real::zero(), |sum, value| sum + value.magnitude().powf(p))
+            .powf(p.recip())
     }
 
-    fn div(self, scalar: T) -> Self {
-        if scalar == T::zero() {
-            panic!("Division by zero is not allowed");
+    /// Unit vector with the same direction, `None` for a zero vector.
+    pub fn normalize(&self) -> Option<Vector<T>> {
+        let norm = self.norm_l2();
+        if norm.is_zero() {
+            return None;
         }
-        
-        let mut result = self.data.clone();
-        for value in &mut result {
-            *value = *value / scalar;
+        let scale = T::from_real(norm);
+        Some(self.data.iter().map(|value| *value / scale).collect())
+    }
+
+    /// Inner product `Σ a_k conj(b_k)`, equal to `dot` for real elements.
+    pub fn inner(&self, other: &Vector<T>) -> T {
+        if self.data.len() != other.data.len() {
+            panic!("Vectors must be of the same length for inner product");
         }
-        Vector { data: result }
+        self.data.iter().zip(&other.data).fold(T::zero(), |sum, (a, b)| sum + *a * b.conjugate())
     }
-}
 
-impl <T: std::ops::Add<Output = T> + Clone + Copy> std::ops::Add for Vector<T> {
-    type Output = Self;
+    /// Cross product of two 3-vectors.
+    pub fn cross(&self, other: &Vector<T>) -> Vector<T> {
+        if self.data.len() != 3 || other.data.len() != 3 {
+            panic!("Cross product is only defined for 3-vectors");
+        }
+        let (a, b) = (&self.data, &other.data);
+        Vector::new(vec![a[1] * b[2] - a[2] * b[1],
+                         a[2] * b[0] - a[0] * b[2],
+                         a[0] * b[1] - a[1] * b[0]])
+    }
+
+    /// Angle in [0, π] between the vectors, from the real part of their
+    /// inner product. `None` if either is a zero vector.
+    pub fn angle(&self, other: &Vector<T>) -> Option<T::Real> {
+        let norms = self.norm_l2() * other.norm_l2();
+        if norms.is_zero() {
+            return None;
+        }
+        let cosine = self.inner(other).real_part() / norms;
+        // Rounding can push the cosine slightly outside [-1, 1].
+        Some(cosine.max(-T::Real::one()).min(T::Real::one()).acos())
+    }
+
+    /// Orthogonal projection of `self` onto the direction of `other`,
+    /// `None` if `other` is a zero vector.
+    pub fn project_onto(&self, other: &Vector<T>) -> Option<Vector<T>> {
+        let other_norm = other.inner(other);
+        if other_norm.is_zero() {
+            return None;
+        }
+        let coefficient = self.inner(other) / other_norm;
+        Some(other.data.iter().map(|value| *value * coefficient).collect())
+    }
+
+    pub fn cumulative_sum(&self) -> Vector<T> {
+        self.data.iter()
+            .scan(T::zero(), |sum, value| {
+                *sum = *sum + *value;
+                Some(*sum)
+            })
+            .collect()
+    }
+
+    pub fn cumulative_product(&self) -> Vector<T> {
+        self.data.iter()
+            .scan(T::one(), |product, value| {
+                *product = *product * *value;
+                Some(*product)
+            })
+            .collect()
+    }
+
+    /// Index of the smallest element (see `VectorElement::order_key`), the
+    /// first one on ties. NaN elements are skipped, `None` if there is none left.
+    pub fn argmin(&self) -> Option<usize> {
+        self.arg_best(|candidate, best| candidate < best)
+    }
+
+    /// Index of the largest element, see `argmin`.
+    pub fn argmax(&self) -> Option<usize> {
+        self.arg_best(|candidate, best| candidate > best)
+    }
+
+    fn arg_best<F: Fn(T::Real, T::Real) -> bool>(&self, is_better: F) -> Option<usize> {
+        let mut best: Option<(usize, T::Real)> = None;
+        for (index, value) in self.data.iter().enumerate() {
+            let key = value.order_key();
+            if key.is_nan() {
+                continue;
+            }
+            match best {
+                Some((_, best_key)) if !is_better(key, best_key) => {}
+                _ => best = Some((index, key)),
+            }
+        }
+        best.map(|(index, _)| index)
+    }
+
+    /// Linear interpolation onto `length` evenly spaced points, the first
+    /// and last elements staying in place.
+    pub fn resample_linear(&self, length: usize) -> Vector<T> {
+        let count = self.data.len();
+        if count == 0 || length == 0 {
+            return Vector { data: Vec::new() };
+        }
+        if count == 1 || length == 1 {
+            return Vector { data: vec![self.data[0]; length] };
+        }
+        let real = |value: usize| <T::Real as num_traits::NumCast>::from(value).unwrap();
+        let step = real(count - 1) / real(length - 1);
+        (0..length)
+            .map(|index| {
+                let position = real(index) * step;
+                let lower = num_traits::ToPrimitive::to_usize(&position.floor()).unwrap().min(count - 2);
+                let fraction = T::from_real(position - real(lower));
+                self.data[lower] + (self.data[lower + 1] - self.data[lower]) * fraction
+            })
+            .collect()
+    }
 
-    fn add(self, other: Self) -> Self {
+    /// Magnitude of each element.
+    pub fn abs(&self) -> Vector<T::Real> {
+        self.data.iter().map(|value| value.magnitude()).collect()
+    }
+
+    /// Principal square root of each element.
+    pub fn sqrt(&self) -> Vector<T> {
+        self.data.iter().map(|value| value.sqrt()).collect()
+    }
+
+    pub fn exp(&self) -> Vector<T> {
+        self.data.iter().map(|value| value.exp()).collect()
+    }
+
+    /// Natural logarithm of each element.
+    pub fn ln(&self) -> Vector<T> {
+        self.data.iter().map(|value| value.ln()).collect()
+    }
+}
+
+/// Non panicking variants of the operators, for data that may be malformed.
+impl<T: Copy> Vector<T> {
+    fn check_dimension(&self, other: &Vector<T>) -> Result<(), VectorError> {
         if self.data.len() != other.data.len() {
-            panic!("Vectors must be of the same length for addition");
+            return Err(VectorError::DimensionMismatch { expected: self.data.len(), found: other.data.len() });
         }
-        
-        let mut result = self.data.clone();
-        for (i, value) in other.data.iter().enumerate() {
-            result[i] = result[i] + *value;
+        Ok(())
+    }
+
+    fn checked_zip<F: Fn(T, T) -> T>(&self, other: &Vector<T>, operation: F) -> Result<Vector<T>, VectorError> {
+        self.check_dimension(other)?;
+        Ok(self.data.iter().zip(&other.data).map(|(a, b)| operation(*a, *b)).collect())
+    }
+
+    pub fn checked_add(&self, other: &Vector<T>) -> Result<Vector<T>, VectorError>
+    where
+        T: std::ops::Add<Output = T>,
+    {
+        self.checked_zip(other, |a, b| a + b)
+    }
+
+    pub fn checked_sub(&self, other: &Vector<T>) -> Result<Vector<T>, VectorError>
+    where
+        T: std::ops::Sub<Output = T>,
+    {
+        self.checked_zip(other, |a, b| a - b)
+    }
+
+    pub fn checked_mul(&self, other: &Vector<T>) -> Result<Vector<T>, VectorError>
+    where
+        T: std::ops::Mul<Output = T>,
+    {
+        self.checked_zip(other, |a, b| a * b)
+    }
+
+    /// Elementwise division, `DivisionByZero` if any element of `other` is zero.
+    pub fn checked_div(&self, other: &Vector<T>) -> Result<Vector<T>, VectorError>
+    where
+        T: std::ops::Div<Output = T> + Zero,
+    {
+        self.check_dimension(other)?;
+        if other.data.iter().any(|value| value.is_zero()) {
+            return Err(VectorError::DivisionByZero);
         }
-        Vector { data: result }
+        self.checked_zip(other, |a, b| a / b)
+    }
+
+    pub fn checked_div_scalar(&self, scalar: T) -> Result<Vector<T>, VectorError>
+    where
+        T: std::ops::Div<Output = T> + Zero,
+    {
+        if scalar.is_zero() {
+            return Err(VectorError::DivisionByZero);
+        }
+        Ok(self.data.iter().map(|value| *value / scalar).collect())
+    }
+
+    pub fn checked_dot(&self, other: &Vector<T>) -> Result<T, VectorError>
+    where
+        T: std::ops::Mul<Output = T> + std::ops::Add<Output = T> + Zero,
+    {
+        self.check_dimension(other)?;
+        Ok(self.data.iter().zip(&other.data).fold(T::zero(), |sum, (a, b)| sum + *a * *b))
     }
 }
 
-impl <T: std::ops::Sub<Output = T> + Clone + Copy> std::ops::Sub for Vector<T> {
-    type Output = Self;
+impl<T: VectorElement> Vector<T> {
+    pub fn checked_inner(&self, other: &Vector<T>) -> Result<T, VectorError> {
+        self.check_dimension(other)?;
+        Ok(self.inner(other))
+    }
 
-    fn sub(self, other: Self) -> Self {
-        if self.data.len() != other.data.len() {
-            panic!("Vectors must be of the same length for subtraction");
+    pub fn checked_cross(&self, other: &Vector<T>) -> Result<Vector<T>, VectorError> {
+        for vector in [self, other] {
+            if vector.data.len() != 3 {
+                return Err(VectorError::DimensionMismatch { expected: 3, found: vector.data.len() });
+            }
+        }
+        Ok(self.cross(other))
+    }
+}
+
+/// Elementwise `Vector op Vector` and `Vector op scalar` operators, by value
+/// (reusing the left buffer) and by reference, plus their compound
+/// assignments. Lengths must match.
+macro_rules! impl_vector_ops {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $operation:literal) => {
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$assign_trait<&Vector<T>> for Vector<T> {
+            fn $assign_method(&mut self, other: &Vector<T>) {
+                if self.data.len() != other.data.len() {
+                    panic!(concat!("Vectors must be of the same length for ", $operation));
+                }
+                for (value, other) in self.data.iter_mut().zip(&other.data) {
+                    *value = std::ops::$trait::$method(*value, *other);
+                }
+            }
+        }
+
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$assign_trait for Vector<T> {
+            fn $assign_method(&mut self, other: Vector<T>) {
+                std::ops::$assign_trait::$assign_method(self, &other);
+            }
+        }
+
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$trait for Vector<T> {
+            type Output = Vector<T>;
+
+            fn $method(mut self, other: Vector<T>) -> Vector<T> {
+                std::ops::$assign_trait::$assign_method(&mut self, &other);
+                self
+            }
+        }
+
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$trait<&Vector<T>> for Vector<T> {
+            type Output = Vector<T>;
+
+            fn $method(mut self, other: &Vector<T>) -> Vector<T> {
+                std::ops::$assign_trait::$assign_method(&mut self, other);
+                self
+            }
+        }
+
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$trait<&Vector<T>> for &Vector<T> {
+            type Output = Vector<T>;
+
+            fn $method(self, other: &Vector<T>) -> Vector<T> {
+                if self.data.len() != other.data.len() {
+                    panic!(concat!("Vectors must be of the same length for ", $operation));
+                }
+                self.data.iter().zip(&other.data).map(|(a, b)| std::ops::$trait::$method(*a, *b)).collect()
+            }
+        }
+
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$assign_trait<T> for Vector<T> {
+            fn $assign_method(&mut self, scalar: T) {
+                for value in self.data.iter_mut() {
+                    *value = std::ops::$trait::$method(*value, scalar);
+                }
+            }
+        }
+
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$trait<T> for Vector<T> {
+            type Output = Vector<T>;
+
+            fn $method(mut self, scalar: T) -> Vector<T> {
+                std::ops::$assign_trait::$assign_method(&mut self, scalar);
+                self
+            }
+        }
+
+        impl<T: Copy + std::ops::$trait<Output = T>> std::ops::$trait<T> for &Vector<T> {
+            type Output = Vector<T>;
+
+            fn $method(self, scalar: T) -> Vector<T> {
+                self.data.iter().map(|value| std::ops::$trait::$method(*value, scalar)).collect()
+            }
         }
-        
-        let mut result = self.data.clone();
-        for (i, value) in other.data.iter().enumerate() {
-            result[i] = result[i] - *value;
+    };
+}
+
+impl_vector_ops!(Add, add, AddAssign, add_assign, "addition");
+impl_vector_ops!(Sub, sub, SubAssign, sub_assign, "subtraction");
+impl_vector_ops!(Mul, mul, MulAssign, mul_assign, "multiplication");
+impl_vector_ops!(Div, div, DivAssign, div_assign, "division");
+
+/// `scalar op Vector` for the primitive float types, which the orphan rule
+/// doesn't allow generically.
+macro_rules! impl_scalar_lhs_ops {
+    ($($scalar:ty),*) => {$(
+        impl std::ops::Add<Vector<$scalar>> for $scalar {
+            type Output = Vector<$scalar>;
+
+            fn add(self, vector: Vector<$scalar>) -> Vector<$scalar> {
+                vector + self
+            }
+        }
+
+        impl std::ops::Sub<Vector<$scalar>> for $scalar {
+            type Output = Vector<$scalar>;
+
+            fn sub(self, vector: Vector<$scalar>) -> Vector<$scalar> {
+                -vector + self
+            }
+        }
+
+        impl std::ops::Mul<Vector<$scalar>> for $scalar {
+            type Output = Vector<$scalar>;
+
+            fn mul(self, vector: Vector<$scalar>) -> Vector<$scalar> {
+                vector * self
+            }
+        }
+    )*};
+}
+
+impl_scalar_lhs_ops!(f32, f64);
+
+impl<T: Copy + std::ops::Neg<Output = T>> std::ops::Neg for Vector<T> {
+    type Output = Vector<T>;
+
+    fn neg(mut self) -> Vector<T> {
+        for value in self.data.iter_mut() {
+            *value = -*value;
+        }
+        self
+    }
+}
+
+impl<T: Copy + std::ops::Neg<Output = T>> std::ops::Neg for &Vector<T> {
+    type Output = Vector<T>;
+
+    fn neg(self) -> Vector<T> {
+        self.data.iter().map(|value| -*value).collect()
+    }
+}
+
+impl<T> std::ops::Deref for Vector<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.data
+    }
+}
+
+impl<T> std::ops::DerefMut for Vector<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+}
+
+impl<T, I: std::slice::SliceIndex<[T]>> std::ops::Index<I> for Vector<T> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &I::Output {
+        &self.data[index]
+    }
+}
+
+impl<T, I: std::slice::SliceIndex<[T]>> std::ops::IndexMut<I> for Vector<T> {
+    fn index_mut(&mut self, index: I) -> &mut I::Output {
+        &mut self.data[index]
+    }
+}
+
+impl<T> From<Vec<T>> for Vector<T> {
+    fn from(data: Vec<T>) -> Self {
+        Vector { data }
+    }
+}
+
+impl<T> FromIterator<T> for Vector<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Vector { data: iter.into_iter().collect() }
+    }
+}
+
+impl<T> IntoIterator for Vector<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Vector<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Vector<T> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gmath::complex::{C64, ComplexTrait};
+
+    #[test]
+    fn test_elementwise_operators() {
+        let a = Vector::new(vec![1.0, 2.0, 3.0]);
+        let b = Vector::new(vec![4.0, 5.0, 6.0]);
+        assert_eq!(&a + &b, Vector::new(vec![5.0, 7.0, 9.0]));
+        assert_eq!(&b - &a, Vector::new(vec![3.0, 3.0, 3.0]));
+        assert_eq!(&a * &b, Vector::new(vec![4.0, 10.0, 18.0]));
+        assert_eq!(&b / &a, Vector::new(vec![4.0, 2.5, 2.0]));
+        assert_eq!(a.clone() * b.clone() + &a, Vector::new(vec![5.0, 12.0, 21.0]));
+        assert_eq!(-&a, Vector::new(vec![-1.0, -2.0, -3.0]));
+
+        let mut c = a.clone();
+        c += &b;
+        c -= a.clone();
+        c *= &b;
+        c /= b;
+        assert_eq!(c, Vector::new(vec![4.0, 5.0, 6.0]));
+    }
+
+    #[test]
+    fn test_scalar_operators() {
+        let a = Vector::new(vec![1.0f32, -2.0]);
+        assert_eq!(&a * 2.0, Vector::new(vec![2.0, -4.0]));
+        assert_eq!(2.0 * a.clone(), Vector::new(vec![2.0, -4.0]));
+        assert_eq!(a.clone() / 4.0, Vector::new(vec![0.25, -0.5]));
+        assert_eq!(1.0 - a.clone(), Vector::new(vec![0.0, 3.0]));
+        let mut b = a + 1.0;
+        b -= 0.5;
+        assert_eq!(b, Vector::new(vec![1.5, -1.5]));
+
+        let signal = Vector::new(vec![C64::new(1.0, 1.0), C64::i()]);
+        assert_eq!(&signal * C64::i(), Vector::new(vec![C64::new(-1.0, 1.0), C64::new(-1.0, 0.0)]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Vectors must be of the same length for multiplication")]
+    fn test_length_mismatch() {
+        let _ = &Vector::new(vec![1, 2]) * &Vector::new(vec![1]);
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-12, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_norms() {
+        let a = Vector::new(vec![3.0, -4.0, 0.0]);
+        assert_eq!(a.norm_l1(), 7.0);
+        assert_eq!(a.norm_l2(), 5.0);
+        assert_eq!(a.norm_inf(), 4.0);
+        assert_close(a.norm_p(3.0), 91.0f64.cbrt());
+        assert_eq!(a.norm_p(f64::INFINITY), 4.0);
+        assert_close(Vector::new(vec![3e200, 4e200]).norm_l2() / 5e200, 1.0);
+
+        let z = Vector::new(vec![C64::new(3.0, 4.0), C64::new(0.0, -12.0)]);
+        assert_eq!(z.norm_l1(), 17.0);
+        assert_eq!(z.norm_l2(), 13.0);
+        assert_eq!(z.norm_inf(), 12.0);
+        assert_close(z.normalize().unwrap().norm_l2(), 1.0);
+        assert!(Vector::new(vec![0.0f32; 3]).normalize().is_none());
+    }
+
+    #[test]
+    fn test_geometry() {
+        let x = Vector::new(vec![1.0, 0.0, 0.0]);
+        let y = Vector::new(vec![0.0, 2.0, 0.0]);
+        assert_eq!(x.cross(&y), Vector::new(vec![0.0, 0.0, 2.0]));
+        assert_close(x.angle(&y).unwrap(), std::f64::consts::FRAC_PI_2);
+        assert_close(x.angle(&(-&x)).unwrap(), std::f64::consts::PI);
+        assert_eq!(x.angle(&x).unwrap(), 0.0);
+        let v = Vector::new(vec![2.0, 3.0, -1.0]);
+        assert_eq!(v.project_onto(&y).unwrap(), Vector::new(vec![0.0, 3.0, 0.0]));
+        assert!(v.project_onto(&Vector::new(vec![0.0; 3])).is_none());
+
+        let u = Vector::new(vec![C64::i(), C64::one()]);
+        let w = Vector::new(vec![C64::new(0.0, 2.0), C64::new(2.0, 0.0)]);
+        assert_eq!(u.inner(&w), C64::new(4.0, 0.0));
+        assert_eq!(u.project_onto(&w).unwrap(), u);
+        assert!(u.angle(&w).unwrap().abs() < 1e-7);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cross product is only defined for 3-vectors")]
+    fn test_cross_needs_3_vectors() {
+        let a = Vector::new(vec![1.0, 2.0]);
+        let _ = a.cross(&a);
+    }
+
+    #[test]
+    fn test_reductions_and_elementwise_functions() {
+        let a = Vector::new(vec![2.0, -1.0, f64::NAN, 5.0, -1.0]);
+        assert_eq!(a.argmin(), Some(1));
+        assert_eq!(a.argmax(), Some(3));
+        assert_eq!(Vector::<f64>::new(vec![]).argmax(), None);
+        let b = Vector::new(vec![1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(b.cumulative_sum(), Vector::new(vec![1.0, 3.0, 6.0, 10.0]));
+        assert_eq!(b.cumulative_product(), Vector::new(vec![1.0, 2.0, 6.0, 24.0]));
+        assert_eq!(Vector::new(vec![-4.0, 9.0]).abs(), Vector::new(vec![4.0, 9.0]));
+        assert_eq!(Vector::new(vec![4.0, 9.0]).sqrt(), Vector::new(vec![2.0, 3.0]));
+        assert_eq!(Vector::new(vec![0.0, 1.0]).exp().ln(), Vector::new(vec![0.0, 1.0]));
+
+        let z = Vector::new(vec![C64::new(-4.0, 0.0), C64::new(0.0, 5.0), C64::new(3.0, 0.0)]);
+        assert_eq!(z.argmax(), Some(1));
+        assert_eq!(z.argmin(), Some(2));
+        assert_eq!(z.abs(), Vector::new(vec![4.0, 5.0, 3.0]));
+        assert_eq!(z.sqrt()[0], C64::new(0.0, 2.0));
+        assert_eq!(z.cumulative_sum()[2], C64::new(-1.0, 5.0));
+        let w = Vector::new(vec![C64::new(0.5, -1.0)]).ln().exp();
+        assert!((w[0] - C64::new(0.5, -1.0)).magnitude() < 1e-12);
+    }
+
+    #[test]
+    fn test_checked_operations() {
+        let a = Vector::new(vec![1.0, 2.0, 3.0]);
+        let b = Vector::new(vec![2.0, 4.0, 8.0]);
+        let short = Vector::new(vec![1.0, 2.0]);
+        let mismatch = VectorError::DimensionMismatch { expected: 3, found: 2 };
+        assert_eq!(a.checked_add(&b), Ok(&a + &b));
+        assert_eq!(a.checked_sub(&short), Err(mismatch.clone()));
+        assert_eq!(a.checked_mul(&b), Ok(Vector::new(vec![2.0, 8.0, 24.0])));
+        assert_eq!(b.checked_div(&a), Ok(Vector::new(vec![2.0, 2.0, 8.0 / 3.0])));
+        assert_eq!(a.checked_div(&Vector::new(vec![1.0, 0.0, 1.0])), Err(VectorError::DivisionByZero));
+        assert_eq!(a.checked_div_scalar(2.0), Ok(Vector::new(vec![0.5, 1.0, 1.5])));
+        assert_eq!(a.checked_div_scalar(0.0), Err(VectorError::DivisionByZero));
+        assert_eq!(a.checked_dot(&b), Ok(34.0));
+        assert_eq!(a.checked_dot(&short), Err(mismatch.clone()));
+        assert_eq!(Vector::new(vec![1, 2]).checked_dot(&Vector::new(vec![3, 4])), Ok(11));
+        assert_eq!(a.checked_inner(&short), Err(mismatch.clone()));
+        assert_eq!(short.checked_cross(&a), Err(mismatch.clone()));
+        assert_eq!(a.checked_cross(&b), Ok(a.cross(&b)));
+        assert_eq!(mismatch.to_string(), "Dimension mismatch: expected 3 elements, found 2");
+    }
+
+    #[test]
+    fn test_slicing_and_concatenation() {
+        let mut a = Vector::new(vec![1, 2, 3, 4]);
+        assert_eq!(a.slice(1..3), Some(&[2, 3][..]));
+        assert_eq!(a.slice(2..), Some(&[3, 4][..]));
+        assert_eq!(a.slice(3..6), None);
+        a.append(&[5]);
+        let b = Vector::concatenate(&[&a[..2], &[0, 0], &a[3..]]);
+        assert_eq!(b, Vector::new(vec![1, 2, 0, 0, 4, 5]));
+        a.reverse();
+        assert_eq!(a.into_vec(), vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_padding_and_shifts() {
+        let mut a = Vector::new(vec![C64::one(), C64::i(), C64::new(2.0, 0.0)]);
+        a.zero_pad(4);
+        assert_eq!(a.len(), 4);
+        assert_eq!(a[3], C64::zero());
+        a.zero_pad(2);
+        assert_eq!(a.len(), 4);
+
+        let mut b = Vector::new(vec![0, 1, 2, 3, 4]);
+        b.fftshift();
+        assert_eq!(b, Vector::new(vec![3, 4, 0, 1, 2]));
+        b.ifftshift();
+        assert_eq!(b, Vector::new(vec![0, 1, 2, 3, 4]));
+        b.circular_shift(-6);
+        assert_eq!(b, Vector::new(vec![1, 2, 3, 4, 0]));
+        let mut even = Vector::new(vec![0, 1, 2, 3]);
+        even.fftshift();
+        assert_eq!(even, Vector::new(vec![2, 3, 0, 1]));
+    }
+
+    #[test]
+    fn test_resampling() {
+        let a = Vector::new(vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(a.decimate(3), Ok(Vector::new(vec![0.0, 3.0, 6.0])));
+        assert_eq!(a.decimate(0), Err(VectorError::ZeroFactor));
+        assert_eq!(Vector::new(vec![1, 2]).upsample(3), Ok(Vector::new(vec![1, 0, 0, 2, 0, 0])));
+        assert_eq!(Vector::new(vec![1, 2]).upsample(0), Err(VectorError::ZeroFactor));
+        assert_eq!(Vector::new(vec![0.0, 2.0, 4.0]).resample_linear(5), Vector::new(vec![0.0, 1.0, 2.0, 3.0, 4.0]));
+        assert_eq!(a.resample_linear(3), Vector::new(vec![0.0, 3.0, 6.0]));
+        assert_eq!(Vector::new(vec![7.0f32]).resample_linear(2), Vector::new(vec![7.0, 7.0]));
+        let z = Vector::new(vec![C64::zero(), C64::new(2.0, -2.0)]).resample_linear(3);
+        assert_eq!(z[1], C64::new(1.0, -1.0));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let vector = Vector::new(vec![1.0f32, -2.0, 3.5]);
+        assert_eq!(serde_json::to_string(&vector).unwrap(), "[1.0,-2.0,3.5]");
+        let decoded: Vector<f32> = bincode::deserialize(&bincode::serialize(&vector).unwrap()).unwrap();
+        assert_eq!(decoded, vector);
+    }
+
+    #[test]
+    fn test_container_traits() {
+        let mut a: Vector<i32> = (1..=4).collect();
+        assert_eq!(a[1], 2);
+        assert_eq!(a[1..3], [2, 3]);
+        a[0] = 10;
+        assert_eq!(a.iter().sum::<i32>(), 19);
+        assert_eq!(a.len(), 4);
+        a.sort_unstable_by(|x, y| y.cmp(x));
+        for value in &mut a {
+            *value *= 2;
         }
-        Vector { data: result }
+        assert_eq!((&a).into_iter().copied().collect::<Vec<_>>(), vec![20, 8, 6, 4]);
+        assert_eq!(a.into_iter().rev().collect::<Vector<_>>(), Vector::from(vec![4, 6, 8, 20]));
     }
-}
\ No newline at end of file
+}
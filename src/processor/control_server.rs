@@ -0,0 +1,357 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+use crate::processor::parameter::{ParameterControl, ParameterRegistry};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Longest accepted command line in bytes, newline excluded. A longer line
+/// closes the connection.
+const MAX_LINE_LENGTH: usize = 4096;
+
+/// Connections served at the same time, further ones are refused.
+const MAX_CONNECTIONS: usize = 16;
+
+const HELP: &str = "BLOCKS | PARAMS <block> | GET <block> <name> | SET <block> <name> <value> | HELP | QUIT";
+
+/// Line based command server exposing a `ParameterRegistry` on a local socket.
+///
+/// Every request is a single line, every answer is a single line starting
+/// with `OK` or `ERR`:
+///
+/// ```text
+/// BLOCKS                        -> OK 1 2 7
+/// PARAMS <block>                -> OK LogLevel
+/// GET <block> <name>            -> OK Error
+/// SET <block> <name> <value>    -> OK
+/// QUIT                          -> closes the connection
+/// ```
+pub struct ControlServer {
+    running: Arc<AtomicBool>,
+    tcp_address: Option<SocketAddr>,
+    #[cfg(unix)]
+    unix_path: Option<PathBuf>,
+    accept_thread: Option<JoinHandle<()>>,
+}
+
+impl ControlServer {
+    /// Listen on a loopback TCP address, typically `127.0.0.1:<port>`.
+    ///
+    /// Commands are not authenticated, so an address resolving to anything
+    /// but loopback is refused with `io::ErrorKind::InvalidInput`.
+    pub fn start_tcp<A: ToSocketAddrs>(registry: ParameterRegistry, address: A) -> io::Result<ControlServer> {
+        let addresses: Vec<SocketAddr> = address.to_socket_addrs()?.collect();
+        if addresses.is_empty() || !addresses.iter().all(|address| address.ip().is_loopback()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "control server only listens on loopback addresses"));
+        }
+        let listener = TcpListener::bind(addresses.as_slice())?;
+        listener.set_nonblocking(true)?;
+        let tcp_address = Some(listener.local_addr()?);
+        let running = Arc::new(AtomicBool::new(true));
+        let accept_thread = spawn_accept_loop(registry, running.clone(),
+                                              move || listener.accept().map(|(stream, _)| stream));
+        Ok(ControlServer {
+            running,
+            tcp_address,
+            #[cfg(unix)]
+            unix_path: None,
+            accept_thread: Some(accept_thread),
+        })
+    }
+
+    /// Listen on a Unix domain socket. The socket file is removed on `stop`.
+    #[cfg(unix)]
+    pub fn start_unix<P: AsRef<Path>>(registry: ParameterRegistry, path: P) -> io::Result<ControlServer> {
+        let listener = UnixListener::bind(path.as_ref())?;
+        listener.set_nonblocking(true)?;
+        let running = Arc::new(AtomicBool::new(true));
+        let accept_thread = spawn_accept_loop(registry, running.clone(),
+                                              move || listener.accept().map(|(stream, _)| stream));
+        Ok(ControlServer {
+            running,
+            tcp_address: None,
+            unix_path: Some(path.as_ref().to_path_buf()),
+            accept_thread: Some(accept_thread),
+        })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.tcp_address
+    }
+
+    /// Stop accepting connections, close the open ones and wait for their
+    /// threads to finish.
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(accept_thread) = self.accept_thread.take() {
+            let _ = accept_thread.join();
+        }
+        #[cfg(unix)]
+        if let Some(path) = self.unix_path.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Stream types the server can accept connections on.
+trait ControlStream: Read + Write + Send + Sized + 'static {
+    fn try_clone_stream(&self) -> io::Result<Self>;
+    fn set_blocking_with_timeout(&self, timeout: Duration) -> io::Result<()>;
+}
+
+impl ControlStream for TcpStream {
+    fn try_clone_stream(&self) -> io::Result<Self> {
+        self.try_clone()
+    }
+    fn set_blocking_with_timeout(&self, timeout: Duration) -> io::Result<()> {
+        self.set_nonblocking(false)?;
+        self.set_read_timeout(Some(timeout))
+    }
+}
+
+#[cfg(unix)]
+impl ControlStream for UnixStream {
+    fn try_clone_stream(&self) -> io::Result<Self> {
+        self.try_clone()
+    }
+    fn set_blocking_with_timeout(&self, timeout: Duration) -> io::Result<()> {
+        self.set_nonblocking(false)?;
+        self.set_read_timeout(Some(timeout))
+    }
+}
+
+fn spawn_accept_loop<S, A>(registry: ParameterRegistry, running: Arc<AtomicBool>, accept: A) -> JoinHandle<()>
+where
+    S: ControlStream,
+    A: Fn() -> io::Result<S> + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut connections: Vec<JoinHandle<()>> = Vec::new();
+        while running.load(Ordering::SeqCst) {
+            match accept() {
+                Ok(mut stream) => {
+                    connections.retain(|connection| !connection.is_finished());
+                    if connections.len() >= MAX_CONNECTIONS {
+                        let _ = writeln!(stream, "ERR too many connections");
+                        continue;
+                    }
+                    let connection_registry = registry.clone();
+                    let connection_running = running.clone();
+                    connections.push(thread::spawn(move || {
+                        let reader = match stream.try_clone_stream() {
+                            Ok(reader) => reader,
+                            Err(_) => return,
+                        };
+                        if stream.set_blocking_with_timeout(POLL_INTERVAL).is_ok() {
+                            serve(&connection_registry, BufReader::new(reader), stream, &connection_running);
+                        }
+                    }));
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                Err(e) => {
+                    // Running out of descriptors or a connection reset before
+                    // accept is transient, keep serving until `stop`.
+                    log::warn!("control server accept failed: {}", e);
+                    thread::sleep(POLL_INTERVAL);
+                }
+            }
+        }
+        // The connections see `running` cleared within a poll interval.
+        running.store(false, Ordering::SeqCst);
+        for connection in connections {
+            let _ = connection.join();
+        }
+    })
+}
+
+fn serve<R: BufRead, W: Write>(registry: &ParameterRegistry, mut reader: R, mut writer: W, running: &AtomicBool) {
+    let mut line = Vec::new();
+    while running.load(Ordering::SeqCst) {
+        // Room for the longest line and its newline: hitting the limit
+        // without a newline means the line is too long.
+        let limit = (MAX_LINE_LENGTH + 1).saturating_sub(line.len()) as u64;
+        match reader.by_ref().take(limit).read_until(b'\n', &mut line) {
+            Ok(0) => break,
+            Ok(_) => {
+                if line.last() != Some(&b'\n') {
+                    if line.len() > MAX_LINE_LENGTH {
+                        let _ = writeln!(writer, "ERR line longer than {} bytes", MAX_LINE_LENGTH);
+                    }
+                    // End of stream, or the line is too long.
+                    break;
+                }
+                let answer = match std::str::from_utf8(&line) {
+                    Ok(text) => match execute_command(registry, text) {
+                        Some(answer) => answer,
+                        None => break,
+                    },
+                    Err(_) => "ERR invalid UTF-8".to_string(),
+                };
+                line.clear();
+                if writeln!(writer, "{}", answer).and_then(|_| writer.flush()).is_err() {
+                    break;
+                }
+            }
+            // Timeouts let the connection notice a `stop`; a partial line
+            // stays in `line` and is completed by the next read.
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => continue,
+            Err(_) => break,
+        }
+    }
+}
+
+/// Execute one command line and return the answer, or `None` on `QUIT`.
+pub fn execute_command(parameter_control: &Mutex<ParameterControl>, line: &str) -> Option<String> {
+    let mut words = line.split_whitespace();
+    let command = match words.next() {
+        Some(command) => command.to_ascii_uppercase(),
+        None => return Some("ERR empty command".to_string()),
+    };
+    let arguments: Vec<&str> = words.collect();
+    let mut parameter_control = parameter_control.lock().unwrap();
+
+    let answer = match (command.as_str(), arguments.as_slice()) {
+        ("QUIT", []) => return None,
+        ("HELP", []) => Ok(HELP.to_string()),
+        ("BLOCKS", []) => {
+            let blocks: Vec<String> = parameter_control.get_blocks().iter().map(|id| id.to_string()).collect();
+            Ok(blocks.join(" "))
+        }
+        ("PARAMS", [block_id]) => parse_block_id(block_id)
+            .and_then(|block_id| parameter_control.get_block_parameters(block_id).map_err(|e| e.to_string()))
+            .map(|names| names.join(" ")),
+        ("GET", [block_id, name]) => parse_block_id(block_id)
+            .and_then(|block_id| parameter_control.get_parameter_text(name, block_id).map_err(|e| e.to_string())),
+        ("SET", [block_id, name, value @ ..]) if !value.is_empty() => parse_block_id(block_id)
+            .and_then(|block_id| {
+                parameter_control.get_block_parameters(block_id).map_err(|e| e.to_string())?;
+                parameter_control.set_parameter_text(name, block_id, &value.join(" "))
+                    .map(|_| String::new())
+                    .map_err(|e| e.to_string())
+            }),
+        _ => Err(format!("invalid command, expected {}", HELP)),
+    };
+    Some(match answer {
+        Ok(text) if text.is_empty() => "OK".to_string(),
+        Ok(text) => format!("OK {}", text),
+        Err(message) => format!("ERR {}", message),
+    })
+}
+
+fn parse_block_id(text: &str) -> Result<u64, String> {
+    text.parse::<u64>().map_err(|_| format!("invalid block id {}", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::logger::{LogLevel, Logger};
+
+    #[test]
+    fn test_execute_command() {
+        let registry = ParameterRegistry::new();
+        let _logger = Logger::with_registry(&registry, 2811, LogLevel::Warning);
+        let control = &registry;
+
+        assert_eq!(execute_command(control, "BLOCKS"), Some("OK 2811".to_string()));
+        assert_eq!(execute_command(control, "params 2811"), Some("OK LogLevel LogRateLimit".to_string()));
+        assert_eq!(execute_command(control, "GET 2811 LogLevel"), Some("OK Warning".to_string()));
+        assert_eq!(execute_command(control, "SET 2811 LogLevel debug"), Some("OK".to_string()));
+        assert_eq!(execute_command(control, "GET 2811 LogLevel"), Some("OK Debug".to_string()));
+        assert_eq!(execute_command(control, "SET 2811 LogLevel verbose"),
+                   Some("ERR Not allowed value for parameter LogLevel".to_string()));
+        assert_eq!(execute_command(control, "SET 999999 LogLevel Info"),
+                   Some("ERR Block 999999 not found".to_string()));
+        assert_eq!(execute_command(control, "GET x LogLevel"), Some("ERR invalid block id x".to_string()));
+        assert!(execute_command(control, "GET 2811").unwrap().starts_with("ERR invalid command"));
+        assert_eq!(execute_command(control, "SET 2811 LogRateLimit 5/1000ms, collapse"), Some("OK".to_string()));
+        assert_eq!(execute_command(control, "GET 2811 LogRateLimit"), Some("OK 5/1000ms,collapse".to_string()));
+        assert_eq!(execute_command(control, "QUIT"), None);
+    }
+
+    #[test]
+    fn test_tcp_server() {
+        let registry = ParameterRegistry::new();
+        let _logger = Logger::with_registry(&registry, 2812, LogLevel::Error);
+        let mut server = ControlServer::start_tcp(registry, "127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+
+        let mut answer = String::new();
+        writeln!(writer, "SET 2812 LogLevel Notice").unwrap();
+        reader.read_line(&mut answer).unwrap();
+        assert_eq!(answer, "OK\n");
+
+        answer.clear();
+        writeln!(writer, "GET 2812 LogLevel").unwrap();
+        reader.read_line(&mut answer).unwrap();
+        assert_eq!(answer, "OK Notice\n");
+
+        writeln!(writer, "QUIT").unwrap();
+        answer.clear();
+        assert_eq!(reader.read_line(&mut answer).unwrap(), 0);
+        server.stop();
+    }
+
+    #[test]
+    fn test_tcp_rejects_non_loopback_address() {
+        for address in ["0.0.0.0:0", "[::]:0", "192.0.2.1:0"] {
+            let error = ControlServer::start_tcp(ParameterRegistry::new(), address).err().unwrap();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{}", address);
+        }
+        let mut server = ControlServer::start_tcp(ParameterRegistry::new(), "localhost:0").unwrap();
+        assert!(server.local_addr().unwrap().ip().is_loopback());
+        server.stop();
+    }
+
+    #[test]
+    fn test_line_length_limit() {
+        let registry = ParameterRegistry::new();
+        let running = AtomicBool::new(true);
+        let mut input = format!("SET 1 LogLevel {}\nBLOCKS\n", "x".repeat(MAX_LINE_LENGTH - 15)).into_bytes();
+        input.extend(vec![b'y'; MAX_LINE_LENGTH + 1]);
+        input.extend(b"\nBLOCKS\n");
+        let mut output = Vec::new();
+        serve(&registry, io::Cursor::new(input), &mut output, &running);
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   format!("ERR Block 1 not found\nOK\nERR line longer than {} bytes\n", MAX_LINE_LENGTH));
+    }
+
+    #[test]
+    fn test_connection_limit() {
+        let mut server = ControlServer::start_tcp(ParameterRegistry::new(), "127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap();
+        let mut connections = Vec::new();
+        for _ in 0..MAX_CONNECTIONS {
+            let mut stream = TcpStream::connect(address).unwrap();
+            writeln!(stream, "BLOCKS").unwrap();
+            let mut answer = String::new();
+            BufReader::new(stream.try_clone().unwrap()).read_line(&mut answer).unwrap();
+            assert_eq!(answer, "OK\n");
+            connections.push(stream);
+        }
+        let mut answer = String::new();
+        BufReader::new(TcpStream::connect(address).unwrap()).read_line(&mut answer).unwrap();
+        assert_eq!(answer, "ERR too many connections\n");
+
+        drop(connections.pop());
+        server.stop();
+        let mut answer = String::new();
+        assert_eq!(BufReader::new(&connections[0]).read_line(&mut answer).unwrap(), 0);
+    }
+}
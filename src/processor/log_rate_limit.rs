@@ -0,0 +1,363 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use crate::processor::log_sink::LogRecord;
+use crate::processor::logger::LogLevel;
+use crate::processor::parameter::{ParameterType, ParameterModel, ParameterMetadata, ParameterValue,
+                                  ParameterRegistry};
+
+/// Maximum number of messages with a rate limit window. Beyond it expired
+/// windows are dropped, then the oldest ones.
+const MAX_TRACKED_MESSAGES: usize = 1024;
+
+/// Rate limiting applied by a `Logger` to the records of its block.
+///
+/// The text representation, used by the control interface and presets, is
+/// `off`, `<max_messages>/<period_ms>ms`, `collapse` or
+/// `<max_messages>/<period_ms>ms,collapse`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LogRateLimit {
+    /// Maximum number of records with the same level and message per period,
+    /// 0 for no limit.
+    pub max_messages: u32,
+    pub period: Duration,
+    /// Collapse consecutive identical records into
+    /// "last message repeated N times".
+    pub collapse_repeats: bool,
+}
+
+impl LogRateLimit {
+    pub fn off() -> Self {
+        LogRateLimit {
+            max_messages: 0,
+            period: Duration::ZERO,
+            collapse_repeats: false,
+        }
+    }
+}
+
+impl Default for LogRateLimit {
+    fn default() -> Self {
+        LogRateLimit::off()
+    }
+}
+
+impl std::fmt::Display for LogRateLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.max_messages, self.collapse_repeats) {
+            (0, false) => write!(f, "off"),
+            (0, true) => write!(f, "collapse"),
+            (max_messages, collapse_repeats) => {
+                write!(f, "{}/{}ms", max_messages, self.period.as_millis())?;
+                if collapse_repeats {
+                    write!(f, ",collapse")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for LogRateLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rate_limit = LogRateLimit::off();
+        for part in s.split(',').map(|part| part.trim()) {
+            match part.to_ascii_lowercase().as_str() {
+                "off" => {}
+                "collapse" => rate_limit.collapse_repeats = true,
+                limit => {
+                    let (max_messages, period) = limit.split_once('/')
+                        .ok_or_else(|| format!("Invalid rate limit {}", s))?;
+                    let period = period.strip_suffix("ms").unwrap_or(period);
+                    rate_limit.max_messages = max_messages.trim().parse()
+                        .map_err(|_| format!("Invalid rate limit {}", s))?;
+                    rate_limit.period = Duration::from_millis(period.trim().parse()
+                        .map_err(|_| format!("Invalid rate limit {}", s))?);
+                }
+            }
+        }
+        Ok(rate_limit)
+    }
+}
+
+#[derive(Clone)]
+pub struct LogRateLimitParameter {
+    name: String,
+    description: String,
+    parameter_type: ParameterType,
+    default_value: LogRateLimit,
+}
+
+impl LogRateLimitParameter {
+    pub fn new() -> LogRateLimitParameter {
+        LogRateLimitParameter::with_registry(&ParameterRegistry::global())
+    }
+    /// Build the model and register it in `registry` instead of the global one.
+    pub fn with_registry(registry: &ParameterRegistry) -> LogRateLimitParameter {
+        let rate_limit_param = LogRateLimitParameter::default();
+        registry.add_parameter_model(Box::new(rate_limit_param.clone()));
+        rate_limit_param
+    }
+}
+
+/// The model alone, registered nowhere.
+impl Default for LogRateLimitParameter {
+    fn default() -> Self {
+        LogRateLimitParameter {
+            name: "LogRateLimit".to_string(),
+            description: "Limit repeated log messages: off, <count>/<period>ms and/or collapse".to_string(),
+            parameter_type: ParameterType::STRING,
+            default_value: LogRateLimit::off(),
+        }
+    }
+}
+
+impl ParameterModel for LogRateLimitParameter {
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+    fn get_description(&self) -> String {
+        self.description.clone()
+    }
+    fn get_param_type(&self) -> ParameterType {
+        self.parameter_type.clone()
+    }
+    fn validate_value(&self, value: &Box<dyn Any + Send>) -> bool {
+        match value.downcast_ref::<LogRateLimit>() {
+            Some(rate_limit) => rate_limit.max_messages == 0 || !rate_limit.period.is_zero(),
+            None => false,
+        }
+    }
+    fn parse_value(&self, text: &str) -> Option<Box<dyn Any + Send>> {
+        text.parse::<LogRateLimit>().ok().map(|rate_limit| Box::new(rate_limit) as Box<dyn Any + Send>)
+    }
+    fn format_value(&self, value: &(dyn Any + Send)) -> Option<String> {
+        value.downcast_ref::<LogRateLimit>().map(|rate_limit| rate_limit.to_string())
+    }
+    fn get_metadata(&self) -> ParameterMetadata {
+        ParameterMetadata {
+            name: self.get_name(),
+            description: self.get_description(),
+            param_type: self.get_param_type(),
+            default_value: Some(ParameterValue::String(self.default_value.to_string())),
+            allowed_values: Vec::new(),
+            min_value: None,
+            max_value: None,
+        }
+    }
+}
+
+struct RateWindow {
+    start: Instant,
+    count: u32,
+    suppressed: u32,
+    block_id: u64,
+}
+
+impl RateWindow {
+    /// The "previous message suppressed N times" record, if any record was suppressed.
+    fn take_summary(&mut self, (severity, message): &(u8, String)) -> Option<LogRecord> {
+        if self.suppressed == 0 {
+            return None;
+        }
+        let level = LogLevel::from_severity(*severity)?;
+        let record = LogRecord::new(level, self.block_id,
+            format!("previous message suppressed {} times: {}", self.suppressed, message));
+        self.suppressed = 0;
+        Some(record)
+    }
+}
+
+/// State of the rate limiting and repeat collapsing of one logger.
+pub struct LogRateLimiter {
+    last_record: Option<LogRecord>,
+    repeats: u32,
+    windows: HashMap<(u8, String), RateWindow>,
+}
+
+impl LogRateLimiter {
+    pub fn new() -> Self {
+        LogRateLimiter {
+            last_record: None,
+            repeats: 0,
+            windows: HashMap::new(),
+        }
+    }
+
+    /// Records to emit for `record`: none when it is suppressed, otherwise
+    /// `record` possibly preceded by a summary of the suppressed ones.
+    pub fn filter(&mut self, record: LogRecord, rate_limit: &LogRateLimit, now: Instant) -> Vec<LogRecord> {
+        let mut records = Vec::new();
+        if rate_limit.collapse_repeats {
+            if let Some(last_record) = &self.last_record {
+                if last_record.level == record.level
+                    && last_record.message == record.message
+                    && last_record.fields == record.fields {
+                    self.repeats += 1;
+                    return records;
+                }
+            }
+            records.extend(self.take_repeated());
+            self.last_record = Some(record.clone());
+        } else {
+            records.extend(self.take_repeated());
+            self.last_record = None;
+        }
+
+        if rate_limit.max_messages > 0 {
+            let key = (record.level.severity(), record.message.clone());
+            if !self.windows.contains_key(&key) && self.windows.len() >= MAX_TRACKED_MESSAGES {
+                records.extend(self.evict_windows(rate_limit, now));
+            }
+            let window = self.windows
+                .entry(key)
+                .or_insert(RateWindow { start: now, count: 0, suppressed: 0, block_id: record.block_id });
+            if now.duration_since(window.start) >= rate_limit.period {
+                records.extend(window.take_summary(&(record.level.severity(), record.message.clone())));
+                *window = RateWindow { start: now, count: 0, suppressed: 0, block_id: record.block_id };
+            }
+            if window.count >= rate_limit.max_messages {
+                window.suppressed += 1;
+                return records;
+            }
+            window.count += 1;
+        }
+        records.push(record);
+        records
+    }
+
+    /// Drop the expired windows, then the oldest ones until there is room for
+    /// a new one. Returns the summaries of the suppressed records they held.
+    fn evict_windows(&mut self, rate_limit: &LogRateLimit, now: Instant) -> Vec<LogRecord> {
+        let mut records = Vec::new();
+        self.windows.retain(|key, window| {
+            if now.duration_since(window.start) < rate_limit.period {
+                return true;
+            }
+            records.extend(window.take_summary(key));
+            false
+        });
+        while self.windows.len() >= MAX_TRACKED_MESSAGES {
+            let oldest = self.windows.iter()
+                .min_by_key(|(_, window)| window.start)
+                .map(|(key, _)| key.clone());
+            let Some(key) = oldest else { break };
+            if let Some(mut window) = self.windows.remove(&key) {
+                records.extend(window.take_summary(&key));
+            }
+        }
+        records
+    }
+
+    /// Every summary still pending: "last message repeated N times" and
+    /// "previous message suppressed N times", e.g. to write them on flush.
+    pub fn take_pending(&mut self) -> Vec<LogRecord> {
+        let mut records: Vec<LogRecord> = self.take_repeated().into_iter().collect();
+        let mut windows: Vec<(&(u8, String), &mut RateWindow)> = self.windows.iter_mut().collect();
+        windows.sort_by_key(|(_, window)| window.start);
+        records.extend(windows.into_iter().filter_map(|(key, window)| window.take_summary(key)));
+        records
+    }
+
+    /// The "last message repeated N times" record pending, if any.
+    pub fn take_repeated(&mut self) -> Option<LogRecord> {
+        if self.repeats == 0 {
+            return None;
+        }
+        let last_record = self.last_record.as_ref()?;
+        let record = LogRecord::new(last_record.level, last_record.block_id,
+                                    format!("last message repeated {} times", self.repeats));
+        self.repeats = 0;
+        Some(record)
+    }
+}
+
+impl Default for LogRateLimiter {
+    fn default() -> Self {
+        LogRateLimiter::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(records: &[LogRecord]) -> Vec<String> {
+        records.iter().map(|record| record.message.clone()).collect()
+    }
+
+    #[test]
+    fn test_rate_limit_text() {
+        assert_eq!("off".parse::<LogRateLimit>(), Ok(LogRateLimit::off()));
+        let rate_limit: LogRateLimit = "10/1000ms, collapse".parse().unwrap();
+        assert_eq!(rate_limit, LogRateLimit { max_messages: 10, period: Duration::from_secs(1), collapse_repeats: true });
+        assert_eq!(rate_limit.to_string(), "10/1000ms,collapse");
+        assert_eq!("collapse".parse::<LogRateLimit>().unwrap().to_string(), "collapse");
+        assert!("often".parse::<LogRateLimit>().is_err());
+    }
+
+    #[test]
+    fn test_collapse_repeats() {
+        let rate_limit: LogRateLimit = "collapse".parse().unwrap();
+        let mut limiter = LogRateLimiter::new();
+        let now = Instant::now();
+        let mut emitted = Vec::new();
+        for message in ["overflow", "overflow", "overflow", "recovered"] {
+            emitted.extend(limiter.filter(LogRecord::new(LogLevel::Warning, 3, message.to_string()), &rate_limit, now));
+        }
+        assert_eq!(messages(&emitted), vec!["overflow", "last message repeated 2 times", "recovered"]);
+        assert!(limiter.take_repeated().is_none());
+    }
+
+    #[test]
+    fn test_rate_limit_per_message() {
+        let rate_limit: LogRateLimit = "2/100ms".parse().unwrap();
+        let mut limiter = LogRateLimiter::new();
+        let start = Instant::now();
+        let mut emitted = Vec::new();
+        for (offset, message) in [(0, "late frame"), (1, "late frame"), (2, "bad crc"), (3, "late frame"),
+                                  (4, "late frame"), (150, "late frame")] {
+            let record = LogRecord::new(LogLevel::Warning, 3, message.to_string());
+            emitted.extend(limiter.filter(record, &rate_limit, start + Duration::from_millis(offset)));
+        }
+        assert_eq!(messages(&emitted), vec!["late frame", "late frame", "bad crc",
+                                            "previous message suppressed 2 times: late frame", "late frame"]);
+    }
+
+    #[test]
+    fn test_pending_summaries() {
+        let rate_limit: LogRateLimit = "1/1000ms".parse().unwrap();
+        let mut limiter = LogRateLimiter::new();
+        let now = Instant::now();
+        for message in ["late frame", "late frame", "late frame", "bad crc"] {
+            limiter.filter(LogRecord::new(LogLevel::Warning, 3, message.to_string()), &rate_limit, now);
+        }
+        let pending = limiter.take_pending();
+        assert_eq!(messages(&pending), vec!["previous message suppressed 2 times: late frame"]);
+        assert_eq!(pending[0].block_id, 3);
+        assert!(limiter.take_pending().is_empty());
+    }
+
+    #[test]
+    fn test_tracked_messages_are_bounded() {
+        let rate_limit: LogRateLimit = "1/1000ms".parse().unwrap();
+        let mut limiter = LogRateLimiter::new();
+        let start = Instant::now();
+        let mut emitted = Vec::new();
+        for index in 0..MAX_TRACKED_MESSAGES + 10 {
+            let now = start + Duration::from_micros(index as u64);
+            for _ in 0..2 {
+                let record = LogRecord::new(LogLevel::Warning, 3, format!("sensor {} lost", index));
+                emitted.extend(limiter.filter(record, &rate_limit, now));
+            }
+        }
+        assert_eq!(limiter.windows.len(), MAX_TRACKED_MESSAGES);
+        let evicted: Vec<String> = messages(&emitted).into_iter()
+            .filter(|message| message.starts_with("previous"))
+            .collect();
+        assert_eq!(evicted.len(), 10);
+        assert_eq!(evicted[0], "previous message suppressed 1 times: sensor 0 lost");
+    }
+}
@@ -0,0 +1,553 @@
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::SystemTime;
+use crate::processor::logger::LogLevel;
+use crate::utils::json::json_string;
+use crate::utils::times::format_rfc3339;
+
+/// Representation of the records written by a sink.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum LogFormat {
+    /// `<timestamp> [<level>] block <id>: <message> key=value ...`
+    #[default]
+    Text,
+    /// One JSON object per line (JSON lines).
+    Json,
+}
+
+/// One message emitted by a `Logger`, with the key/value fields attached
+/// at the call site.
+#[derive(Clone, Debug)]
+pub struct LogRecord {
+    pub timestamp: SystemTime,
+    pub level: LogLevel,
+    pub block_id: u64,
+    pub message: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl LogRecord {
+    pub fn new(level: LogLevel, block_id: u64, message: String) -> Self {
+        LogRecord {
+            timestamp: SystemTime::now(),
+            level,
+            block_id,
+            message,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn format(&self, format: LogFormat) -> String {
+        match format {
+            LogFormat::Text => self.to_string(),
+            LogFormat::Json => self.to_json(),
+        }
+    }
+
+    /// `{"timestamp": ..., "level": ..., "block_id": ..., "message": ..., "fields": {...}}`
+    pub fn to_json(&self) -> String {
+        let fields: Vec<String> = self.fields.iter()
+            .map(|(key, value)| format!("{}:{}", json_string(key), json_string(value)))
+            .collect();
+        format!("{{\"timestamp\":{},\"level\":{},\"block_id\":{},\"message\":{},\"fields\":{{{}}}}}",
+                json_string(&format_rfc3339(self.timestamp)),
+                json_string(&self.level.to_string()),
+                self.block_id,
+                json_string(&self.message),
+                fields.join(","))
+    }
+}
+
+impl std::fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}] block {}: {}", format_rfc3339(self.timestamp), self.level, self.block_id, self.message)?;
+        for (key, value) in &self.fields {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Destination of log records.
+pub trait LogSink {
+    fn write(&mut self, record: &LogRecord) -> io::Result<()>;
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct StderrSink {
+    format: LogFormat,
+}
+
+impl StderrSink {
+    pub fn new(format: LogFormat) -> Self {
+        StderrSink { format }
+    }
+}
+
+impl LogSink for StderrSink {
+    fn write(&mut self, record: &LogRecord) -> io::Result<()> {
+        writeln!(io::stderr().lock(), "{}", record.format(self.format))
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+/// File sink rotating `path` to `path.1`, `path.2`, ... once it reaches
+/// `max_bytes`. At most `max_files` rotated files are kept.
+pub struct RotatingFileSink {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+    format: LogFormat,
+}
+
+impl RotatingFileSink {
+    pub fn new<P: AsRef<Path>>(path: P, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFileSink {
+            path,
+            max_bytes,
+            max_files,
+            file,
+            size,
+            format: LogFormat::Text,
+        })
+    }
+
+    pub fn set_format(&mut self, format: LogFormat) {
+        self.format = format;
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl LogSink for RotatingFileSink {
+    fn write(&mut self, record: &LogRecord) -> io::Result<()> {
+        let line = format!("{}\n", record.format(self.format));
+        if self.size > 0 && self.size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// In-memory sink keeping the last `capacity` formatted records.
+/// Clones share the same buffer, so a clone can be kept to read it back.
+#[derive(Clone)]
+pub struct RingBufferSink {
+    capacity: usize,
+    lines: Arc<Mutex<VecDeque<String>>>,
+    format: LogFormat,
+}
+
+impl RingBufferSink {
+    pub fn new(capacity: usize) -> Self {
+        RingBufferSink {
+            capacity,
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            format: LogFormat::Text,
+        }
+    }
+
+    pub fn set_format(&mut self, format: LogFormat) {
+        self.format = format;
+    }
+
+    pub fn get_lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.lines.lock().unwrap().clear();
+    }
+}
+
+impl LogSink for RingBufferSink {
+    fn write(&mut self, record: &LogRecord) -> io::Result<()> {
+        if self.capacity == 0 {
+            return Ok(());
+        }
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(record.format(self.format));
+        Ok(())
+    }
+}
+
+/// Syslog facilities usable by a `SyslogSink`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SyslogFacility {
+    User,
+    Daemon,
+    Local0,
+    Local1,
+    Local2,
+    Local3,
+    Local4,
+    Local5,
+    Local6,
+    Local7,
+}
+
+impl SyslogFacility {
+    pub fn code(&self) -> u8 {
+        match self {
+            SyslogFacility::User => 1,
+            SyslogFacility::Daemon => 3,
+            SyslogFacility::Local0 => 16,
+            SyslogFacility::Local1 => 17,
+            SyslogFacility::Local2 => 18,
+            SyslogFacility::Local3 => 19,
+            SyslogFacility::Local4 => 20,
+            SyslogFacility::Local5 => 21,
+            SyslogFacility::Local6 => 22,
+            SyslogFacility::Local7 => 23,
+        }
+    }
+}
+
+enum SyslogTransport {
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+    Udp(UdpSocket),
+}
+
+/// Structured data element carrying the block id, `[grade@32473 blockId="42"]`.
+/// 32473 is the private enterprise number reserved for documentation.
+const SYSLOG_STRUCTURED_DATA_ID: &str = "grade@32473";
+
+/// Sink sending RFC 5424 messages to a local syslog socket or a UDP collector.
+///
+/// The `LogLevel` maps to the syslog severity and the block id is sent as
+/// structured data, so records can be filtered per block by the collector.
+/// In `LogFormat::Text` the record fields are added to the structured data,
+/// in `LogFormat::Json` the message part is the JSON record.
+pub struct SyslogSink {
+    transport: SyslogTransport,
+    facility: SyslogFacility,
+    hostname: String,
+    app_name: String,
+    process_id: u32,
+    format: LogFormat,
+}
+
+impl SyslogSink {
+    /// Connect to the local syslog daemon through `/dev/log`.
+    #[cfg(unix)]
+    pub fn local(facility: SyslogFacility) -> io::Result<Self> {
+        SyslogSink::unix("/dev/log", facility)
+    }
+
+    #[cfg(unix)]
+    pub fn unix<P: AsRef<Path>>(path: P, facility: SyslogFacility) -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(path)?;
+        Ok(SyslogSink::with_transport(SyslogTransport::Unix(socket), facility))
+    }
+
+    pub fn udp<A: ToSocketAddrs>(address: A, facility: SyslogFacility) -> io::Result<Self> {
+        let address = address.to_socket_addrs()?.next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no syslog address"))?;
+        let local: SocketAddr = if address.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(address)?;
+        Ok(SyslogSink::with_transport(SyslogTransport::Udp(socket), facility))
+    }
+
+    fn with_transport(transport: SyslogTransport, facility: SyslogFacility) -> Self {
+        let hostname = fs::read_to_string("/proc/sys/kernel/hostname")
+            .map(|name| name.trim().to_string())
+            .ok()
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "-".to_string());
+        let app_name = std::env::current_exe().ok()
+            .and_then(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "-".to_string());
+        SyslogSink {
+            transport,
+            facility,
+            hostname,
+            app_name,
+            process_id: std::process::id(),
+            format: LogFormat::Text,
+        }
+    }
+
+    pub fn set_format(&mut self, format: LogFormat) {
+        self.format = format;
+    }
+
+    pub fn set_hostname(&mut self, hostname: String) {
+        self.hostname = hostname;
+    }
+
+    pub fn set_app_name(&mut self, app_name: String) {
+        self.app_name = app_name;
+    }
+
+    /// RFC 5424 representation of `record`.
+    pub fn format(&self, record: &LogRecord) -> String {
+        let priority = self.facility.code() as u32 * 8 + record.level.severity() as u32;
+        let mut structured_data = format!("[{} blockId=\"{}\"", SYSLOG_STRUCTURED_DATA_ID, record.block_id);
+        let message = match self.format {
+            LogFormat::Text => {
+                for (key, value) in &record.fields {
+                    let name: String = key.chars()
+                        .filter(|c| c.is_ascii_graphic() && !matches!(c, '=' | ']' | '"'))
+                        .take(32)
+                        .collect();
+                    if !name.is_empty() {
+                        structured_data.push_str(&format!(" {}=\"{}\"", name, syslog_param_value(value)));
+                    }
+                }
+                record.message.clone()
+            }
+            LogFormat::Json => record.to_json(),
+        };
+        structured_data.push(']');
+        format!("<{}>1 {} {} {} {} - {} {}",
+                priority,
+                format_rfc3339(record.timestamp),
+                syslog_header_field(&self.hostname, 255),
+                syslog_header_field(&self.app_name, 48),
+                self.process_id,
+                structured_data,
+                message)
+    }
+}
+
+impl LogSink for SyslogSink {
+    fn write(&mut self, record: &LogRecord) -> io::Result<()> {
+        let message = self.format(record);
+        match &self.transport {
+            #[cfg(unix)]
+            SyslogTransport::Unix(socket) => socket.send(message.as_bytes()).map(|_| ()),
+            SyslogTransport::Udp(socket) => socket.send(message.as_bytes()).map(|_| ()),
+        }
+    }
+}
+
+/// Header fields are printable US-ASCII without spaces, `-` when empty.
+fn syslog_header_field(value: &str, max_length: usize) -> String {
+    let field: String = value.chars().filter(|c| c.is_ascii_graphic()).take(max_length).collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
+
+/// Structured data parameter values escape `"`, `\\` and `]`.
+fn syslog_param_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Shared set of sinks records are dispatched to.
+///
+/// Like `ParameterRegistry`, there is a process-wide default
+/// (`LogSinks::global`, writing to stderr) and independent sets can be
+/// created with `LogSinks::new` and given to loggers.
+#[derive(Clone)]
+pub struct LogSinks {
+    sinks: Arc<Mutex<Vec<Box<dyn LogSink + Send>>>>,
+}
+
+static LOG_SINKS: OnceLock<LogSinks> = OnceLock::new();
+
+impl LogSinks {
+    pub fn new() -> Self {
+        LogSinks {
+            sinks: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn global() -> Self {
+        LOG_SINKS.get_or_init(|| {
+            let sinks = LogSinks::new();
+            sinks.add_sink(Box::new(StderrSink::default()));
+            sinks
+        }).clone()
+    }
+
+    pub fn add_sink(&self, sink: Box<dyn LogSink + Send>) {
+        self.lock_sinks().push(sink);
+    }
+
+    pub fn clear(&self) {
+        self.lock_sinks().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock_sinks().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock_sinks().is_empty()
+    }
+
+    /// Write `record` to every sink. A failing sink doesn't prevent the
+    /// others from receiving the record; logging never fails the caller.
+    pub fn dispatch(&self, record: &LogRecord) {
+        for sink in self.lock_sinks().iter_mut() {
+            let _ = sink.write(record);
+        }
+    }
+
+    pub fn flush(&self) {
+        for sink in self.lock_sinks().iter_mut() {
+            let _ = sink.flush();
+        }
+    }
+
+    fn lock_sinks(&self) -> MutexGuard<'_, Vec<Box<dyn LogSink + Send>>> {
+        self.sinks.lock().unwrap()
+    }
+}
+
+impl Default for LogSinks {
+    fn default() -> Self {
+        LogSinks::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn record(message: &str) -> LogRecord {
+        LogRecord {
+            timestamp: UNIX_EPOCH + Duration::from_secs(86400),
+            level: LogLevel::Warning,
+            block_id: 7,
+            message: message.to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_record_formats() {
+        let mut record = record("Track \"lost\"");
+        record.fields.push(("track_id".to_string(), "12".to_string()));
+        record.fields.push(("snr_db".to_string(), "3.5".to_string()));
+        assert_eq!(record.format(LogFormat::Text),
+                   "1970-01-02T00:00:00.000000Z [Warning] block 7: Track \"lost\" track_id=12 snr_db=3.5");
+        assert_eq!(record.format(LogFormat::Json),
+                   "{\"timestamp\":\"1970-01-02T00:00:00.000000Z\",\"level\":\"Warning\",\"block_id\":7,\
+                    \"message\":\"Track \\\"lost\\\"\",\"fields\":{\"track_id\":\"12\",\"snr_db\":\"3.5\"}}");
+
+        let mut sink = RingBufferSink::new(1);
+        sink.set_format(LogFormat::Json);
+        sink.write(&record).unwrap();
+        assert!(sink.get_lines()[0].starts_with("{\"timestamp\""));
+    }
+
+    #[test]
+    fn test_ring_buffer_sink() {
+        let mut sink = RingBufferSink::new(2);
+        for message in ["first", "second", "third"] {
+            sink.write(&record(message)).unwrap();
+        }
+        assert_eq!(sink.get_lines(), vec![
+            "1970-01-02T00:00:00.000000Z [Warning] block 7: second".to_string(),
+            "1970-01-02T00:00:00.000000Z [Warning] block 7: third".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_syslog_sink() {
+        let collector = UdpSocket::bind("127.0.0.1:0").unwrap();
+        collector.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let mut sink = SyslogSink::udp(collector.local_addr().unwrap(), SyslogFacility::Local0).unwrap();
+        sink.set_hostname("radar host".to_string());
+        sink.set_app_name("".to_string());
+        sink.write(&record("Frame dropped")).unwrap();
+
+        let mut buffer = [0u8; 512];
+        let length = collector.recv(&mut buffer).unwrap();
+        assert_eq!(std::str::from_utf8(&buffer[..length]).unwrap(),
+                   format!("<132>1 1970-01-02T00:00:00.000000Z radarhost - {} - [grade@32473 blockId=\"7\"] Frame dropped",
+                           std::process::id()));
+
+        let mut record = record("Frame dropped");
+        record.fields.push(("reason".to_string(), "queue \"full\"]".to_string()));
+        assert!(sink.format(&record).ends_with(
+            "[grade@32473 blockId=\"7\" reason=\"queue \\\"full\\\"\\]\"] Frame dropped"));
+    }
+
+    #[test]
+    fn test_rotating_file_sink() {
+        let directory = std::env::temp_dir().join(format!("grade_processor_rotation_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("processor.log");
+
+        // Each line is 52 bytes, so every file holds a single record.
+        let mut sink = RotatingFileSink::new(&path, 100, 2).unwrap();
+        for message in ["0000", "1111", "2222", "3333"] {
+            sink.write(&record(message)).unwrap();
+        }
+        sink.flush().unwrap();
+
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap();
+        assert!(read(path.clone()).ends_with("3333\n"));
+        assert!(read(sink.rotated_path(1)).ends_with("2222\n"));
+        assert!(read(sink.rotated_path(2)).ends_with("1111\n"));
+        assert!(!sink.rotated_path(3).exists());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,339 @@
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use crate::processor::log_sink::{LogRecord, LogSink, LogSinks};
+
+/// What `AsyncLogWriter` does with a record when its queue is full.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LogOverflowPolicy {
+    /// Discard the oldest queued record to make room for the new one.
+    DropOldest,
+    /// Discard the new record.
+    DropNewest,
+    /// Wait until the writer thread makes room. Never loses records but can
+    /// stall the caller.
+    Block,
+}
+
+struct WriterQueue {
+    records: VecDeque<LogRecord>,
+    dropped: u64,
+    flush_requested: u64,
+    flush_done: u64,
+    shutdown: bool,
+}
+
+struct WriterState {
+    queue: Mutex<WriterQueue>,
+    /// Signaled when records, a flush request or the shutdown are queued.
+    work_available: Condvar,
+    /// Signaled when the writer thread has emptied the queue or completed a flush.
+    progress: Condvar,
+}
+
+impl WriterState {
+    fn lock_queue(&self) -> MutexGuard<'_, WriterQueue> {
+        self.queue.lock().unwrap()
+    }
+}
+
+/// State shared by the clones of an `AsyncLogWriter`. Dropping it, with the
+/// last handle, shuts the writer thread down.
+struct WriterShared {
+    state: Arc<WriterState>,
+    capacity: usize,
+    overflow_policy: LogOverflowPolicy,
+    writer_thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl WriterShared {
+    fn shutdown(&self) {
+        let writer_thread = self.writer_thread.lock().unwrap().take();
+        if let Some(writer_thread) = writer_thread {
+            self.state.lock_queue().shutdown = true;
+            self.state.work_available.notify_one();
+            let _ = writer_thread.join();
+        }
+    }
+}
+
+impl Drop for WriterShared {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Sink handing records to a background thread that writes them to `LogSinks`,
+/// so logging from a processing block never waits on disk or network I/O.
+///
+/// The writer is a handle: add a clone to the logger sinks and keep one to
+/// read the statistics and call `shutdown` on exit, e.g. when the sinks are
+/// the never dropped `LogSinks::global()`.
+///
+/// `flush` waits until every record queued before the call is written and the
+/// destination sinks are flushed. `shutdown`, or dropping the last handle,
+/// writes the remaining records before stopping the thread.
+///
+/// The destination must not contain the writer itself.
+#[derive(Clone)]
+pub struct AsyncLogWriter {
+    shared: Arc<WriterShared>,
+}
+
+impl AsyncLogWriter {
+    pub fn new(sinks: LogSinks, capacity: usize, overflow_policy: LogOverflowPolicy) -> Self {
+        let state = Arc::new(WriterState {
+            queue: Mutex::new(WriterQueue {
+                records: VecDeque::with_capacity(capacity),
+                dropped: 0,
+                flush_requested: 0,
+                flush_done: 0,
+                shutdown: false,
+            }),
+            work_available: Condvar::new(),
+            progress: Condvar::new(),
+        });
+        let thread_state = state.clone();
+        let writer_thread = thread::spawn(move || write_loop(&thread_state, &sinks));
+        AsyncLogWriter {
+            shared: Arc::new(WriterShared {
+                state,
+                capacity: capacity.max(1),
+                overflow_policy,
+                writer_thread: Mutex::new(Some(writer_thread)),
+            }),
+        }
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    pub fn get_overflow_policy(&self) -> LogOverflowPolicy {
+        self.shared.overflow_policy
+    }
+
+    /// Number of records discarded because the queue was full.
+    pub fn get_dropped_count(&self) -> u64 {
+        self.shared.state.lock_queue().dropped
+    }
+
+    /// Number of records waiting to be written.
+    pub fn get_queued_count(&self) -> usize {
+        self.shared.state.lock_queue().records.len()
+    }
+
+    /// Write the queued records, flush the destination and stop the writer
+    /// thread. Later records are rejected by every handle.
+    pub fn shutdown(&self) {
+        self.shared.shutdown();
+    }
+}
+
+impl LogSink for AsyncLogWriter {
+    fn write(&mut self, record: &LogRecord) -> io::Result<()> {
+        let shared = &self.shared;
+        let mut queue = shared.state.lock_queue();
+        if queue.shutdown {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "log writer is shut down"));
+        }
+        if queue.records.len() >= shared.capacity {
+            match shared.overflow_policy {
+                LogOverflowPolicy::DropOldest => {
+                    queue.records.pop_front();
+                    queue.dropped += 1;
+                }
+                LogOverflowPolicy::DropNewest => {
+                    queue.dropped += 1;
+                    return Ok(());
+                }
+                LogOverflowPolicy::Block => {
+                    queue = shared.state.progress
+                        .wait_while(queue, |queue| queue.records.len() >= shared.capacity && !queue.shutdown)
+                        .unwrap();
+                    if queue.shutdown {
+                        return Err(io::Error::new(io::ErrorKind::BrokenPipe, "log writer is shut down"));
+                    }
+                }
+            }
+        }
+        queue.records.push_back(record.clone());
+        drop(queue);
+        shared.state.work_available.notify_one();
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let state = &self.shared.state;
+        let mut queue = state.lock_queue();
+        if queue.shutdown {
+            return Ok(());
+        }
+        queue.flush_requested += 1;
+        let flush_id = queue.flush_requested;
+        state.work_available.notify_one();
+        let _queue = state.progress
+            .wait_while(queue, |queue| queue.flush_done < flush_id && !queue.shutdown)
+            .unwrap();
+        Ok(())
+    }
+}
+
+fn write_loop(state: &WriterState, sinks: &LogSinks) {
+    loop {
+        let mut queue = state.work_available
+            .wait_while(state.lock_queue(), |queue| {
+                queue.records.is_empty() && queue.flush_done == queue.flush_requested && !queue.shutdown
+            })
+            .unwrap();
+        let records: Vec<LogRecord> = queue.records.drain(..).collect();
+        let flush_requested = queue.flush_requested;
+        let shutdown = queue.shutdown;
+        drop(queue);
+        // Producers blocked on a full queue can continue while we write.
+        state.progress.notify_all();
+
+        for record in &records {
+            sinks.dispatch(record);
+        }
+        let mut queue = state.lock_queue();
+        if queue.flush_done < flush_requested || (shutdown && queue.records.is_empty()) {
+            drop(queue);
+            sinks.flush();
+            queue = state.lock_queue();
+            queue.flush_done = flush_requested;
+        }
+        let finished = shutdown && queue.records.is_empty();
+        drop(queue);
+        state.progress.notify_all();
+        if finished {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::processor::log_sink::RingBufferSink;
+    use crate::processor::logger::{LogLevel, Logger};
+    use crate::processor::parameter::ParameterRegistry;
+
+    /// Sink stalling until its gate is opened, standing in for slow I/O.
+    struct GatedSink {
+        gate: Arc<(Mutex<bool>, Condvar)>,
+        buffer: RingBufferSink,
+    }
+
+    impl LogSink for GatedSink {
+        fn write(&mut self, record: &LogRecord) -> io::Result<()> {
+            let (open, opened) = &*self.gate;
+            let _open = opened.wait_while(open.lock().unwrap(), |open| !*open).unwrap();
+            self.buffer.write(record)
+        }
+    }
+
+    fn gated_writer(capacity: usize, policy: LogOverflowPolicy)
+                    -> (AsyncLogWriter, Arc<(Mutex<bool>, Condvar)>, RingBufferSink) {
+        let gate = Arc::new((Mutex::new(false), Condvar::new()));
+        let buffer = RingBufferSink::new(64);
+        let destination = LogSinks::new();
+        destination.add_sink(Box::new(GatedSink { gate: gate.clone(), buffer: buffer.clone() }));
+        (AsyncLogWriter::new(destination, capacity, policy), gate, buffer)
+    }
+
+    fn open_gate(gate: &(Mutex<bool>, Condvar)) {
+        *gate.0.lock().unwrap() = true;
+        gate.1.notify_all();
+    }
+
+    /// Queue `messages` once the writer thread is stalled on "first".
+    fn fill(writer: &mut AsyncLogWriter, messages: &[&str]) {
+        writer.write(&LogRecord::new(LogLevel::Info, 1, "first".to_string())).unwrap();
+        while writer.get_queued_count() > 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        for message in messages {
+            writer.write(&LogRecord::new(LogLevel::Info, 1, message.to_string())).unwrap();
+        }
+    }
+
+    fn messages(buffer: &RingBufferSink) -> Vec<String> {
+        buffer.get_lines().iter().map(|line| line.rsplit(": ").next().unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn test_logger_flush_and_shutdown() {
+        let destination = LogSinks::new();
+        let buffer = RingBufferSink::new(16);
+        destination.add_sink(Box::new(buffer.clone()));
+        let writer = AsyncLogWriter::new(destination, 8, LogOverflowPolicy::Block);
+        let sinks = LogSinks::new();
+        sinks.add_sink(Box::new(writer.clone()));
+        let logger = Logger::with_sinks(&ParameterRegistry::new(), sinks.clone(), 4, LogLevel::Info);
+
+        logger.info("queued");
+        logger.flush();
+        assert_eq!(messages(&buffer), vec!["queued"]);
+
+        // The sinks still hold a handle, as the global ones would.
+        logger.warning("written on shutdown");
+        writer.shutdown();
+        assert_eq!(messages(&buffer), vec!["queued", "written on shutdown"]);
+        assert_eq!(writer.get_queued_count(), 0);
+        logger.error("after shutdown");
+        assert_eq!(messages(&buffer).len(), 2);
+        assert_eq!(sinks.len(), 1);
+    }
+
+    #[test]
+    fn test_last_handle_drop_shuts_down() {
+        let destination = LogSinks::new();
+        let buffer = RingBufferSink::new(16);
+        destination.add_sink(Box::new(buffer.clone()));
+        let sinks = LogSinks::new();
+        sinks.add_sink(Box::new(AsyncLogWriter::new(destination, 8, LogOverflowPolicy::Block)));
+        sinks.dispatch(&LogRecord::new(LogLevel::Info, 1, "written on drop".to_string()));
+        sinks.clear();
+        assert_eq!(messages(&buffer), vec!["written on drop"]);
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let (mut writer, gate, buffer) = gated_writer(2, LogOverflowPolicy::DropOldest);
+        fill(&mut writer, &["a", "b", "c"]);
+        assert_eq!(writer.get_dropped_count(), 1);
+        open_gate(&gate);
+        writer.flush().unwrap();
+        assert_eq!(messages(&buffer), vec!["first", "b", "c"]);
+    }
+
+    #[test]
+    fn test_drop_newest() {
+        let (mut writer, gate, buffer) = gated_writer(2, LogOverflowPolicy::DropNewest);
+        fill(&mut writer, &["a", "b", "c"]);
+        assert_eq!(writer.get_dropped_count(), 1);
+        open_gate(&gate);
+        writer.shutdown();
+        assert_eq!(messages(&buffer), vec!["first", "a", "b"]);
+    }
+
+    #[test]
+    fn test_block() {
+        let (mut writer, gate, buffer) = gated_writer(2, LogOverflowPolicy::Block);
+        let opener = {
+            let gate = gate.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                open_gate(&gate);
+            })
+        };
+        fill(&mut writer, &["a", "b", "c"]);
+        opener.join().unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.get_dropped_count(), 0);
+        assert_eq!(messages(&buffer), vec!["first", "a", "b", "c"]);
+    }
+}
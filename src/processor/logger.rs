@@ -1,9 +1,13 @@
 use std::any::Any;
-use std::sync::OnceLock;
-use crate::processor::parameter::{ParameterType, ParameterModel, Parameter,
-                                  add_parameter_model, add_parameter};
+use std::sync::{Arc, Mutex, Weak};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Instant;
+use crate::processor::parameter::{ParameterType, ParameterModel, ParameterMetadata, ParameterValue,
+                                  ParameterRegistry, ParameterListener, GLOBAL_BLOCK_ID};
+use crate::processor::log_sink::{LogRecord, LogSinks};
+use crate::processor::log_rate_limit::{LogRateLimit, LogRateLimitParameter, LogRateLimiter};
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum LogLevel {
     Emergency,
     Alert,
@@ -15,6 +19,97 @@ pub enum LogLevel {
     Debug,
 }
 
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            LogLevel::Emergency => "Emergency",
+            LogLevel::Alert => "Alert",
+            LogLevel::Critical => "Critical",
+            LogLevel::Error => "Error",
+            LogLevel::Warning => "Warning",
+            LogLevel::Notice => "Notice",
+            LogLevel::Info => "Info",
+            LogLevel::Debug => "Debug",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl LogLevel {
+    /// Syslog severity code, from 0 (Emergency) to 7 (Debug).
+    pub fn severity(&self) -> u8 {
+        match self {
+            LogLevel::Emergency => 0,
+            LogLevel::Alert => 1,
+            LogLevel::Critical => 2,
+            LogLevel::Error => 3,
+            LogLevel::Warning => 4,
+            LogLevel::Notice => 5,
+            LogLevel::Info => 6,
+            LogLevel::Debug => 7,
+        }
+    }
+
+    pub fn from_severity(severity: u8) -> Option<LogLevel> {
+        match severity {
+            0 => Some(LogLevel::Emergency),
+            1 => Some(LogLevel::Alert),
+            2 => Some(LogLevel::Critical),
+            3 => Some(LogLevel::Error),
+            4 => Some(LogLevel::Warning),
+            5 => Some(LogLevel::Notice),
+            6 => Some(LogLevel::Info),
+            7 => Some(LogLevel::Debug),
+            _ => None,
+        }
+    }
+
+    /// Whether a message of this level passes a logger configured at `threshold`.
+    pub fn is_enabled_for(&self, threshold: LogLevel) -> bool {
+        self.severity() <= threshold.severity()
+    }
+}
+
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => LogLevel::Error,
+            log::Level::Warn => LogLevel::Warning,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Debug | log::Level::Trace => LogLevel::Debug,
+        }
+    }
+}
+
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Emergency | LogLevel::Alert | LogLevel::Critical | LogLevel::Error => log::Level::Error,
+            LogLevel::Warning => log::Level::Warn,
+            LogLevel::Notice | LogLevel::Info => log::Level::Info,
+            LogLevel::Debug => log::Level::Debug,
+        }
+    }
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "emergency" => Ok(LogLevel::Emergency),
+            "alert" => Ok(LogLevel::Alert),
+            "critical" => Ok(LogLevel::Critical),
+            "error" => Ok(LogLevel::Error),
+            "warning" => Ok(LogLevel::Warning),
+            "notice" => Ok(LogLevel::Notice),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            _ => Err(format!("Unknown log level {}", s)),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct  LogLevelParameter {
     name: String,
@@ -27,6 +122,10 @@ pub struct  LogLevelParameter {
 }
 impl LogLevelParameter {
     pub fn new() -> LogLevelParameter {
+        LogLevelParameter::with_registry(&ParameterRegistry::global())
+    }
+    /// Build the model and register it in `registry` instead of the global one.
+    pub fn with_registry(registry: &ParameterRegistry) -> LogLevelParameter {
         let log_level_param = LogLevelParameter {
             name: "LogLevel".to_string(),
             description: "Set the level of log to apply".to_string(),
@@ -49,7 +148,7 @@ impl LogLevelParameter {
             max_value: LogLevel::Emergency,
         };
         let box_param: Box<dyn ParameterModel+Send+'static> = Box::new(log_level_param.clone());
-        add_parameter_model(box_param);
+        registry.add_parameter_model(box_param);
         log_level_param
     }
     pub fn get_min_value(&self) -> LogLevel {
@@ -73,29 +172,538 @@ impl ParameterModel for LogLevelParameter {
         self.parameter_type.clone()
     }
     fn validate_value(&self, value: &Box<dyn Any + Send>) -> bool {
-        let value = value.downcast_ref::<LogLevel>().unwrap();
+        match value.downcast_ref::<LogLevel>() {
+            Some(level) => self.get_allowed_values().contains(level),
+            None => false,
+        }
+    }
+    fn parse_value(&self, text: &str) -> Option<Box<dyn Any + Send>> {
+        text.parse::<LogLevel>().ok().map(|level| Box::new(level) as Box<dyn Any + Send>)
+    }
+    fn format_value(&self, value: &(dyn Any + Send)) -> Option<String> {
+        value.downcast_ref::<LogLevel>().map(|level| level.to_string())
+    }
+    fn get_metadata(&self) -> ParameterMetadata {
+        ParameterMetadata {
+            name: self.get_name(),
+            description: self.get_description(),
+            param_type: self.get_param_type(),
+            default_value: Some(ParameterValue::String(self.default_level.to_string())),
+            allowed_values: self.allowed_levels.iter()
+                .map(|level| ParameterValue::String(level.to_string()))
+                .collect(),
+            min_value: Some(ParameterValue::String(self.min_value.to_string())),
+            max_value: Some(ParameterValue::String(self.max_value.to_string())),
+        }
+    }
+}
+/// Log level applied to every block instead of its own `LogLevel`, stored on
+/// `GLOBAL_BLOCK_ID`. `None` means no override.
+#[derive(Clone)]
+pub struct LogLevelOverrideParameter {
+    name: String,
+    description: String,
+    parameter_type: ParameterType,
+}
 
-        if self.get_allowed_values().contains(value) {
-            true
-        } else {
-            false
+impl LogLevelOverrideParameter {
+    pub fn new() -> LogLevelOverrideParameter {
+        LogLevelOverrideParameter::with_registry(&ParameterRegistry::global())
+    }
+    /// Build the model and register it in `registry` instead of the global one.
+    pub fn with_registry(registry: &ParameterRegistry) -> LogLevelOverrideParameter {
+        let override_param = LogLevelOverrideParameter::default();
+        registry.add_parameter_model(Box::new(override_param.clone()));
+        override_param
+    }
+}
+
+/// The model alone, registered nowhere.
+impl Default for LogLevelOverrideParameter {
+    fn default() -> Self {
+        LogLevelOverrideParameter {
+            name: "LogLevelOverride".to_string(),
+            description: "Log level applied to every block, None to use the level of each block".to_string(),
+            parameter_type: ParameterType::ENUMERATION,
         }
     }
 }
+
+impl ParameterModel for LogLevelOverrideParameter {
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+    fn get_description(&self) -> String {
+        self.description.clone()
+    }
+    fn get_param_type(&self) -> ParameterType {
+        self.parameter_type.clone()
+    }
+    fn validate_value(&self, value: &Box<dyn Any + Send>) -> bool {
+        value.downcast_ref::<Option<LogLevel>>().is_some()
+    }
+    fn parse_value(&self, text: &str) -> Option<Box<dyn Any + Send>> {
+        if text.eq_ignore_ascii_case("none") {
+            return Some(Box::new(None::<LogLevel>));
+        }
+        text.parse::<LogLevel>().ok().map(|level| Box::new(Some(level)) as Box<dyn Any + Send>)
+    }
+    fn format_value(&self, value: &(dyn Any + Send)) -> Option<String> {
+        value.downcast_ref::<Option<LogLevel>>()
+            .map(|level| level.map_or("None".to_string(), |level| level.to_string()))
+    }
+    fn get_metadata(&self) -> ParameterMetadata {
+        let mut allowed_values = vec![ParameterValue::String("None".to_string())];
+        allowed_values.extend((0..8).filter_map(LogLevel::from_severity)
+            .map(|level| ParameterValue::String(level.to_string())));
+        ParameterMetadata {
+            name: self.get_name(),
+            description: self.get_description(),
+            param_type: self.get_param_type(),
+            default_value: Some(ParameterValue::String("None".to_string())),
+            allowed_values,
+            min_value: None,
+            max_value: None,
+        }
+    }
+}
+
+/// Set (or clear with `None`) the log level override of every logger of `registry`.
+pub fn set_log_level_override(registry: &ParameterRegistry, log_level: Option<LogLevel>) -> bool {
+    if !registry.lock().unwrap().has_parameter_model("LogLevelOverride") {
+        LogLevelOverrideParameter::with_registry(registry);
+    }
+    registry.add_parameter("LogLevelOverride".to_string(), GLOBAL_BLOCK_ID, Box::new(log_level))
+}
+
+const NO_LOG_LEVEL_OVERRIDE: u8 = u8::MAX;
+
+/// Block settings shared between a `Logger` and its parameter listener, so
+/// changes made through `ParameterControl` apply immediately.
+struct LoggerSettings {
+    log_level: AtomicU8,
+    log_level_override: AtomicU8,
+    rate_limit: Mutex<LogRateLimit>,
+}
+
 pub struct Logger {
-    log_level: Parameter<LogLevel>,
+    block_id: u64,
+    settings: Arc<LoggerSettings>,
+    rate_limiter: Mutex<LogRateLimiter>,
+    registry: ParameterRegistry,
+    sinks: LogSinks,
 }
 
 impl Logger {
     pub fn new(block_id: u64, log_level: LogLevel) -> Self {
-        static LOG_LEVEL_PARAM: OnceLock<LogLevelParameter> = OnceLock::new();
-        LOG_LEVEL_PARAM.get_or_init(|| LogLevelParameter::new());
+        Logger::with_registry(&ParameterRegistry::global(), block_id, log_level)
+    }
+
+    pub fn with_registry(registry: &ParameterRegistry, block_id: u64, log_level: LogLevel) -> Self {
+        Logger::with_sinks(registry, LogSinks::global(), block_id, log_level)
+    }
+
+    pub fn with_sinks(registry: &ParameterRegistry, sinks: LogSinks, block_id: u64, log_level: LogLevel) -> Self {
+        if !registry.lock().unwrap().has_parameter_model("LogLevel") {
+            LogLevelParameter::with_registry(registry);
+        }
+        if !registry.lock().unwrap().has_parameter_model("LogRateLimit") {
+            LogRateLimitParameter::with_registry(registry);
+        }
+        if !registry.lock().unwrap().has_parameter_model("LogLevelOverride") {
+            LogLevelOverrideParameter::with_registry(registry);
+        }
         let value: Box<dyn Any + Send> = Box::new(log_level);
-            if !add_parameter("LogLevel".to_string(), block_id, value) {
+            if !registry.add_parameter("LogLevel".to_string(), block_id, value) {
                 panic!("Not allowed value")
             }
+
+        // Read the current rate limit and override and subscribe under the
+        // same lock so no change can be missed in between. A logger created
+        // again for the same block keeps the rate limit already configured.
+        let mut parameter_control = registry.lock().unwrap();
+        let rate_limit = match parameter_control.get_parameter_value::<LogRateLimit>("LogRateLimit", block_id) {
+            Some(rate_limit) => rate_limit,
+            None => {
+                let rate_limit = LogRateLimit::off();
+                if parameter_control.set_parameter("LogRateLimit", block_id, Box::new(rate_limit)).is_err() {
+                    panic!("Not allowed value")
+                }
+                rate_limit
+            }
+        };
+        let log_level_override = parameter_control
+            .get_parameter_value::<Option<LogLevel>>("LogLevelOverride", GLOBAL_BLOCK_ID)
+            .flatten();
+        let settings = Arc::new(LoggerSettings {
+            log_level: AtomicU8::new(log_level.severity()),
+            log_level_override: AtomicU8::new(log_level_override.map_or(NO_LOG_LEVEL_OVERRIDE, |level| level.severity())),
+            rate_limit: Mutex::new(rate_limit),
+        });
+        parameter_control.add_listener(Logger::settings_listener(block_id, Arc::downgrade(&settings)));
+        drop(parameter_control);
+
         Logger{
-            log_level: Parameter::new("LogLevel".to_string(),block_id,log_level)
+            block_id,
+            settings,
+            rate_limiter: Mutex::new(LogRateLimiter::new()),
+            registry: registry.clone(),
+            sinks,
+        }
+    }
+
+    fn settings_listener(block_id: u64, settings: Weak<LoggerSettings>) -> ParameterListener {
+        Box::new(move |parameter_name, changed_block_id, value| {
+            let settings = match settings.upgrade() {
+                Some(settings) => settings,
+                None => return false,
+            };
+            match parameter_name {
+                "LogLevel" if changed_block_id == block_id => {
+                    if let Some(level) = value.downcast_ref::<LogLevel>() {
+                        settings.log_level.store(level.severity(), Ordering::Relaxed);
+                    }
+                }
+                "LogRateLimit" if changed_block_id == block_id => {
+                    if let Some(rate_limit) = value.downcast_ref::<LogRateLimit>() {
+                        *settings.rate_limit.lock().unwrap() = *rate_limit;
+                    }
+                }
+                "LogLevelOverride" if changed_block_id == GLOBAL_BLOCK_ID => {
+                    if let Some(level) = value.downcast_ref::<Option<LogLevel>>() {
+                        let severity = level.map_or(NO_LOG_LEVEL_OVERRIDE, |level| level.severity());
+                        settings.log_level_override.store(severity, Ordering::Relaxed);
+                    }
+                }
+                _ => {}
+            }
+            true
+        })
+    }
+
+    pub fn get_block_id(&self) -> u64 {
+        self.block_id
+    }
+
+    /// Level configured for the block, see also `get_effective_log_level`.
+    pub fn get_log_level(&self) -> LogLevel {
+        LogLevel::from_severity(self.settings.log_level.load(Ordering::Relaxed)).unwrap_or(LogLevel::Debug)
+    }
+
+    /// Change the level of the block through the parameter registry.
+    pub fn set_log_level(&self, log_level: LogLevel) -> bool {
+        self.registry.add_parameter("LogLevel".to_string(), self.block_id, Box::new(log_level))
+    }
+
+    pub fn get_log_level_override(&self) -> Option<LogLevel> {
+        LogLevel::from_severity(self.settings.log_level_override.load(Ordering::Relaxed))
+    }
+
+    /// Level actually applied: the override if one is set, otherwise the block level.
+    pub fn get_effective_log_level(&self) -> LogLevel {
+        self.get_log_level_override().unwrap_or_else(|| self.get_log_level())
+    }
+
+    pub fn get_rate_limit(&self) -> LogRateLimit {
+        *self.settings.rate_limit.lock().unwrap()
+    }
+
+    /// Change the rate limit of the block, returns false if it isn't valid.
+    pub fn set_rate_limit(&self, rate_limit: LogRateLimit) -> bool {
+        self.registry.add_parameter("LogRateLimit".to_string(), self.block_id, Box::new(rate_limit))
+    }
+
+    pub fn get_sinks(&self) -> &LogSinks {
+        &self.sinks
+    }
+
+    pub fn is_enabled(&self, level: LogLevel) -> bool {
+        level.is_enabled_for(self.get_effective_log_level())
+    }
+
+    pub fn log(&self, level: LogLevel, message: &str) {
+        self.log_with_fields(level, message, &[]);
+    }
+
+    /// Log `message` with key/value fields, e.g.
+    /// `logger.log_with_fields(LogLevel::Warning, "Track lost", &[("track_id", &12)])`.
+    pub fn log_with_fields(&self, level: LogLevel, message: &str, fields: &[(&str, &dyn std::fmt::Display)]) {
+        if !self.is_enabled(level) {
+            return;
         }
+        let mut record = LogRecord::new(level, self.get_block_id(), message.to_string());
+        record.fields = fields.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+        let records = self.rate_limiter.lock().unwrap().filter(record, &self.get_rate_limit(), Instant::now());
+        for record in &records {
+            self.sinks.dispatch(record);
+        }
+    }
+
+    pub fn emergency(&self, message: &str) {
+        self.log(LogLevel::Emergency, message);
+    }
+
+    pub fn alert(&self, message: &str) {
+        self.log(LogLevel::Alert, message);
+    }
+
+    pub fn critical(&self, message: &str) {
+        self.log(LogLevel::Critical, message);
+    }
+
+    pub fn error(&self, message: &str) {
+        self.log(LogLevel::Error, message);
     }
-}
\ No newline at end of file
+
+    pub fn warning(&self, message: &str) {
+        self.log(LogLevel::Warning, message);
+    }
+
+    pub fn notice(&self, message: &str) {
+        self.log(LogLevel::Notice, message);
+    }
+
+    pub fn info(&self, message: &str) {
+        self.log(LogLevel::Info, message);
+    }
+
+    pub fn debug(&self, message: &str) {
+        self.log(LogLevel::Debug, message);
+    }
+
+    /// Write the pending "last message repeated" and "previous message
+    /// suppressed" records, then flush the sinks.
+    pub fn flush(&self) {
+        for record in self.rate_limiter.lock().unwrap().take_pending() {
+            self.sinks.dispatch(&record);
+        }
+        self.sinks.flush();
+    }
+}
+
+/// A `Logger` receives the records of the `log` facade at its block's level.
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.is_enabled(metadata.level().into())
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            self.log(record.level().into(), &record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {
+        Logger::flush(self);
+    }
+}
+
+/// `log::Log` implementation routing facade records to block loggers.
+///
+/// A record goes to the logger registered for the longest matching target
+/// prefix (module path, e.g. `my_app::tracker`), or to the default logger.
+/// Each logger filters with its own block level.
+pub struct LogBridge {
+    default_logger: Logger,
+    target_loggers: Vec<(String, Logger)>,
+}
+
+impl LogBridge {
+    pub fn new(default_logger: Logger) -> Self {
+        LogBridge {
+            default_logger,
+            target_loggers: Vec::new(),
+        }
+    }
+
+    pub fn add_target(&mut self, target: String, logger: Logger) {
+        self.target_loggers.push((target, logger));
+        self.target_loggers.sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+    }
+
+    pub fn get_logger(&self, target: &str) -> &Logger {
+        self.target_loggers.iter()
+            .find(|(prefix, _)| {
+                target == prefix || (target.starts_with(prefix.as_str()) && target[prefix.len()..].starts_with("::"))
+            })
+            .map(|(_, logger)| logger)
+            .unwrap_or(&self.default_logger)
+    }
+
+    /// Install the bridge as the logger of the `log` facade.
+    pub fn install(self) -> Result<(), log::SetLoggerError> {
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(log::LevelFilter::Trace);
+        Ok(())
+    }
+}
+
+impl log::Log for LogBridge {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        log::Log::enabled(self.get_logger(metadata.target()), metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        log::Log::log(self.get_logger(record.target()), record);
+    }
+
+    fn flush(&self) {
+        self.default_logger.flush();
+        for (_, logger) in &self.target_loggers {
+            logger.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::log_sink::RingBufferSink;
+
+    #[test]
+    fn test_logger_filters_by_level() {
+        let registry = ParameterRegistry::new();
+        let sinks = LogSinks::new();
+        let buffer = RingBufferSink::new(16);
+        sinks.add_sink(Box::new(buffer.clone()));
+        let logger = Logger::with_sinks(&registry, sinks, 42, LogLevel::Warning);
+
+        logger.emergency("emergency");
+        logger.error("error");
+        logger.warning("warning");
+        logger.notice("notice");
+        logger.debug("debug");
+
+        let lines = buffer.get_lines();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with(" [Emergency] block 42: emergency"));
+        assert!(lines[1].ends_with(" [Error] block 42: error"));
+        assert!(lines[2].ends_with(" [Warning] block 42: warning"));
+        assert!(logger.is_enabled(LogLevel::Critical));
+        assert!(!logger.is_enabled(LogLevel::Info));
+
+        buffer.clear();
+        logger.log_with_fields(LogLevel::Alert, "Track lost", &[("track_id", &12), ("snr_db", &3.5)]);
+        logger.log_with_fields(LogLevel::Info, "Track updated", &[("track_id", &12)]);
+        assert_eq!(buffer.get_lines().len(), 1);
+        assert!(buffer.get_lines()[0].ends_with(" [Alert] block 42: Track lost track_id=12 snr_db=3.5"));
+    }
+
+    #[test]
+    fn test_logger_rate_limit() {
+        let registry = ParameterRegistry::new();
+        let sinks = LogSinks::new();
+        let buffer = RingBufferSink::new(16);
+        sinks.add_sink(Box::new(buffer.clone()));
+        let logger = Logger::with_sinks(&registry, sinks, 5, LogLevel::Info);
+        assert_eq!(registry.lock().unwrap().get_parameter_text("LogRateLimit", 5), Ok("off".to_string()));
+
+        let invalid = LogRateLimit { max_messages: 3, period: std::time::Duration::ZERO, collapse_repeats: false };
+        assert!(!logger.set_rate_limit(invalid));
+        assert!(logger.set_rate_limit("100/60000ms,collapse".parse().unwrap()));
+        for _ in 0..4 {
+            logger.warning("queue full");
+        }
+        logger.flush();
+        let lines = buffer.get_lines();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" [Warning] block 5: queue full"));
+        assert!(lines[1].ends_with(" [Warning] block 5: last message repeated 3 times"));
+
+        assert!(logger.set_rate_limit("1/60000ms".parse().unwrap()));
+        logger.error("link down");
+        logger.error("link down");
+        logger.flush();
+        let lines = buffer.get_lines();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[3].ends_with(" [Error] block 5: previous message suppressed 1 times: link down"));
+    }
+
+    #[test]
+    fn test_logger_recreated_keeps_rate_limit() {
+        let registry = ParameterRegistry::new();
+        let first = Logger::with_sinks(&registry, LogSinks::new(), 6, LogLevel::Info);
+        let rate_limit: LogRateLimit = "5/1000ms,collapse".parse().unwrap();
+        assert!(first.set_rate_limit(rate_limit));
+        drop(first);
+
+        let second = Logger::with_sinks(&registry, LogSinks::new(), 6, LogLevel::Info);
+        assert_eq!(second.get_rate_limit(), rate_limit);
+        assert_eq!(registry.lock().unwrap().get_parameter_text("LogRateLimit", 6),
+                   Ok("5/1000ms,collapse".to_string()));
+        assert!(second.set_rate_limit(LogRateLimit::off()));
+        assert_eq!(second.get_rate_limit(), LogRateLimit::off());
+    }
+
+    #[test]
+    fn test_log_level_changes_apply_immediately() {
+        let registry = ParameterRegistry::new();
+        let sinks = LogSinks::new();
+        let buffer = RingBufferSink::new(16);
+        sinks.add_sink(Box::new(buffer.clone()));
+        let first = Logger::with_sinks(&registry, sinks.clone(), 1, LogLevel::Error);
+        let second = Logger::with_sinks(&registry, sinks.clone(), 2, LogLevel::Error);
+
+        assert!(registry.lock().unwrap().set_parameter_text("LogLevel", 1, "Debug").is_ok());
+        assert_eq!(first.get_log_level(), LogLevel::Debug);
+        assert_eq!(second.get_log_level(), LogLevel::Error);
+        assert!(second.set_log_level(LogLevel::Notice));
+        assert_eq!(second.get_log_level(), LogLevel::Notice);
+
+        assert!(set_log_level_override(&registry, Some(LogLevel::Critical)));
+        let third = Logger::with_sinks(&registry, sinks.clone(), 3, LogLevel::Debug);
+        for logger in [&first, &second, &third] {
+            assert_eq!(logger.get_effective_log_level(), LogLevel::Critical);
+            logger.error("hidden by override");
+        }
+        assert!(buffer.get_lines().is_empty());
+
+        assert!(registry.lock().unwrap().set_parameter_text("LogLevelOverride", GLOBAL_BLOCK_ID, "none").is_ok());
+        first.debug("visible again");
+        third.info("visible too");
+        assert_eq!(buffer.get_lines().len(), 2);
+
+        assert!(registry.lock().unwrap().set_parameter_text("LogRateLimit", 1, "collapse").is_ok());
+        assert!(first.get_rate_limit().collapse_repeats);
+    }
+
+    #[test]
+    fn test_log_level_rejects_wrong_value_type() {
+        let registry = ParameterRegistry::new();
+        let logger = Logger::with_registry(&registry, 1, LogLevel::Info);
+        let result = registry.lock().unwrap().set_parameters(vec![(1, "LogLevel".to_string(), Box::new(3u8))]);
+        assert_eq!(result, Err(crate::processor::parameter::ParameterError::InvalidValue("LogLevel".to_string())));
+        assert!(!registry.add_parameter("LogLevel".to_string(), 1, Box::new("Debug")));
+        assert_eq!(logger.get_log_level(), LogLevel::Info);
+        assert!(registry.lock().is_ok());
+    }
+
+    #[test]
+    fn test_log_bridge_routes_by_target() {
+        let registry = ParameterRegistry::new();
+        let sinks = LogSinks::new();
+        let buffer = RingBufferSink::new(16);
+        sinks.add_sink(Box::new(buffer.clone()));
+        let mut bridge = LogBridge::new(Logger::with_sinks(&registry, sinks.clone(), 1, LogLevel::Error));
+        bridge.add_target("app::tracker".to_string(), Logger::with_sinks(&registry, sinks.clone(), 2, LogLevel::Debug));
+
+        let emit = |target: &str, level: log::Level, message: &str| {
+            log::Log::log(&bridge, &log::Record::builder()
+                .target(target)
+                .level(level)
+                .args(format_args!("{}", message))
+                .build());
+        };
+        emit("app::tracker::kalman", log::Level::Debug, "tracker debug");
+        emit("app::trackers", log::Level::Warn, "other warning");
+        emit("app", log::Level::Error, "app error");
+        emit("app::tracker", log::Level::Trace, "tracker trace");
+
+        let lines = buffer.get_lines();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with(" [Debug] block 2: tracker debug"));
+        assert!(lines[1].ends_with(" [Error] block 1: app error"));
+        assert!(lines[2].ends_with(" [Debug] block 2: tracker trace"));
+        assert!(!log::Log::enabled(&bridge, &log::Metadata::builder().target("app").level(log::Level::Info).build()));
+    }
+}
@@ -1,3 +1,8 @@
 pub mod parameter;
 pub mod processing;
-pub mod logger;
\ No newline at end of file
+pub mod logger;
+pub mod log_sink;
+pub mod log_rate_limit;
+pub mod log_writer;
+pub mod control_server;
+pub mod preset;
\ No newline at end of file
@@ -1,7 +1,8 @@
 use std::any::Any;
-use std::sync::{Mutex, OnceLock};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
 use std::collections::HashMap;
-#[derive(Clone)]
+use crate::utils::json::json_string;
+#[derive(Clone, Debug, PartialEq)]
 pub enum ParameterType {
     NUMBER,
     ENUMERATION,
@@ -9,12 +10,179 @@ pub enum ParameterType {
     STRING,
 }
 
+impl ParameterType {
+    fn json_type(&self) -> &'static str {
+        match self {
+            ParameterType::NUMBER => "number",
+            ParameterType::ENUMERATION => "string",
+            ParameterType::BOOLEAN => "boolean",
+            ParameterType::STRING => "string",
+        }
+    }
+}
+
+/// Type-erased parameter value as exposed to tooling.
+/// Enumeration values are represented by their name.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParameterValue {
+    Number(f64),
+    Boolean(bool),
+    String(String),
+}
+
+impl ParameterValue {
+    fn to_json(&self) -> String {
+        match self {
+            ParameterValue::Number(value) if value.is_finite() => value.to_string(),
+            ParameterValue::Number(_) => "null".to_string(),
+            ParameterValue::Boolean(value) => value.to_string(),
+            ParameterValue::String(value) => json_string(value),
+        }
+    }
+}
+
+/// Description of a parameter model, used to export the parameter schema.
+#[derive(Clone, Debug)]
+pub struct ParameterMetadata {
+    pub name: String,
+    pub description: String,
+    pub param_type: ParameterType,
+    pub default_value: Option<ParameterValue>,
+    pub allowed_values: Vec<ParameterValue>,
+    pub min_value: Option<ParameterValue>,
+    pub max_value: Option<ParameterValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterError {
+    UnknownParameter(String),
+    InvalidValue(String),
+    ConstraintViolated(String),
+    UnknownBlock(u64),
+    NoTextRepresentation(String),
+    UnknownPreset(String),
+}
+
+impl std::fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParameterError::UnknownParameter(name) => write!(f, "Parameter {} not found", name),
+            ParameterError::InvalidValue(name) => write!(f, "Not allowed value for parameter {}", name),
+            ParameterError::ConstraintViolated(name) => write!(f, "Constraint {} violated", name),
+            ParameterError::UnknownBlock(block_id) => write!(f, "Block {} not found", block_id),
+            ParameterError::NoTextRepresentation(name) => write!(f, "Parameter {} has no text representation", name),
+            ParameterError::UnknownPreset(name) => write!(f, "Preset {} not found", name),
+        }
+    }
+}
+
+impl std::error::Error for ParameterError {}
+
 pub trait ParameterModel {
     fn get_name(&self) -> String;
     fn get_description(&self) -> String;
     fn get_param_type(&self) -> ParameterType;
     fn validate_value(&self, value: &Box<dyn Any + Send>) -> bool;
+    /// Rules involving this parameter and other parameters of the same block.
+    fn get_constraints(&self) -> Vec<ParameterConstraint> {
+        Vec::new()
+    }
+    /// Parse a value from text, as received by the control interface.
+    fn parse_value(&self, _text: &str) -> Option<Box<dyn Any + Send>> {
+        None
+    }
+    /// Text representation of a value, the inverse of `parse_value`.
+    fn format_value(&self, _value: &(dyn Any + Send)) -> Option<String> {
+        None
+    }
+    /// Default, allowed values and bounds of the parameter. Models only
+    /// need to override this when they have such information.
+    fn get_metadata(&self) -> ParameterMetadata {
+        ParameterMetadata {
+            name: self.get_name(),
+            description: self.get_description(),
+            param_type: self.get_param_type(),
+            default_value: None,
+            allowed_values: Vec::new(),
+            min_value: None,
+            max_value: None,
+        }
+    }
+}
+
+/// Values of the parameters of one block, as seen by a `ParameterConstraint`.
+pub struct BlockParameters<'a> {
+    values: HashMap<&'a str, &'a (dyn Any + Send)>,
+}
+
+impl<'a> BlockParameters<'a> {
+    pub fn get<T: 'static>(&self, name: &str) -> Option<&'a T> {
+        self.values.get(name).and_then(|value| value.downcast_ref::<T>())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+}
+
+/// Rule evaluated across several parameters of the same block.
+///
+/// A constraint is only checked once every parameter it refers to has a value
+/// on the block, so a block can still be configured one parameter at a time.
+#[derive(Clone)]
+pub struct ParameterConstraint {
+    name: String,
+    description: String,
+    parameters: Vec<String>,
+    rule: Arc<dyn Fn(&BlockParameters) -> bool + Send + Sync>,
+}
+
+impl ParameterConstraint {
+    pub fn new<F>(name: String, description: String, parameters: Vec<String>, rule: F) -> Self
+    where
+        F: Fn(&BlockParameters) -> bool + Send + Sync + 'static,
+    {
+        ParameterConstraint {
+            name,
+            description,
+            parameters,
+            rule: Arc::new(rule),
+        }
+    }
+
+    /// Constraint between two typed parameters, e.g. `fft_size >= window_length`.
+    /// A value of an unexpected type never satisfies the rule.
+    pub fn between<A, B, F>(name: String, description: String, first: String, second: String, rule: F) -> Self
+    where
+        A: 'static,
+        B: 'static,
+        F: Fn(&A, &B) -> bool + Send + Sync + 'static,
+    {
+        let (first_name, second_name) = (first.clone(), second.clone());
+        ParameterConstraint::new(name, description, vec![first, second], move |values| {
+            match (values.get::<A>(&first_name), values.get::<B>(&second_name)) {
+                (Some(a), Some(b)) => rule(a, b),
+                _ => false,
+            }
+        })
+    }
 
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+    pub fn get_description(&self) -> String {
+        self.description.clone()
+    }
+    pub fn get_parameters(&self) -> &Vec<String> {
+        &self.parameters
+    }
+
+    pub fn is_satisfied(&self, values: &BlockParameters) -> bool {
+        if !self.parameters.iter().all(|name| values.contains(name)) {
+            return true;
+        }
+        (self.rule)(values)
+    }
 }
 
 pub struct Parameter<T> {
@@ -38,36 +206,503 @@ impl<T: Copy> Parameter<T> {
         self.value
     }
 }
+/// Block id holding the parameters that apply to every block, such as the
+/// log level override.
+pub const GLOBAL_BLOCK_ID: u64 = u64::MAX;
+
+/// Called with the parameter name, block id and new value each time a value
+/// is stored. Returning false removes the listener.
+pub type ParameterListener = Box<dyn FnMut(&str, u64, &(dyn Any + Send)) -> bool + Send>;
+
 pub struct ParameterControl {
     parameter_model_table: HashMap<String, Box<dyn ParameterModel + Send>>,
-    parameter_list: HashMap<u64, Box<dyn Any + Send>>,
+    parameter_constraint_table: HashMap<String, Vec<ParameterConstraint>>,
+    parameter_list: HashMap<u64, HashMap<String, Box<dyn Any + Send>>>,
+    parameter_listeners: Vec<ParameterListener>,
 }
 
-static PARAMETER_CONTROL: OnceLock<Mutex<ParameterControl>> = OnceLock::new();
+static PARAMETER_CONTROL: OnceLock<Arc<Mutex<ParameterControl>>> = OnceLock::new();
+
+fn global_control() -> &'static Arc<Mutex<ParameterControl>> {
+    PARAMETER_CONTROL.get_or_init(|| Arc::new(Mutex::new(ParameterControl::new())))
+}
 
 impl ParameterControl {
-    pub fn get() -> &'static Mutex<ParameterControl> {
-        PARAMETER_CONTROL.get_or_init(|| Mutex::new(ParameterControl {
+    pub fn new() -> Self {
+        ParameterControl {
             parameter_model_table: HashMap::new(),
+            parameter_constraint_table: HashMap::new(),
             parameter_list: HashMap::new(),
-        }))
+            parameter_listeners: Vec::new(),
+        }
+    }
+
+    /// Process-wide default registry, see `ParameterRegistry::global`.
+    pub fn get() -> &'static Mutex<ParameterControl> {
+        global_control()
+    }
+
+    pub fn has_parameter_model(&self, parameter_name: &str) -> bool {
+        self.parameter_model_table.contains_key(parameter_name)
+    }
+
+    /// Register `listener` to be notified of every value change. Listeners
+    /// run with the registry locked and must not access it.
+    pub fn add_listener(&mut self, listener: ParameterListener) {
+        self.parameter_listeners.push(listener);
+    }
+
+    pub fn add_parameter_model(&mut self, parameter_model: Box<dyn ParameterModel + Send>) {
+        let name = parameter_model.get_name();
+        self.parameter_constraint_table.insert(name.clone(), parameter_model.get_constraints());
+        self.parameter_model_table.insert(name, parameter_model);
+    }
+
+    /// Validate `value` against its model and against every constraint of the
+    /// block, then store it. On error the block is left unchanged.
+    pub fn set_parameter(&mut self, parameter_name: &str, block_id: u64, value: Box<dyn Any + Send>)
+        -> Result<(), ParameterError> {
+        self.validate_parameter(parameter_name, &value)?;
+        let candidates = HashMap::from([(parameter_name, value.as_ref())]);
+        self.check_constraints(block_id, &candidates)?;
+        self.parameter_list
+            .entry(block_id)
+            .or_default()
+            .insert(parameter_name.to_string(), value);
+        self.notify_listeners(parameter_name, block_id);
+        Ok(())
+    }
+
+    /// Set several values, possibly on several blocks, as a single update:
+    /// every value and every block constraint is checked against the final
+    /// state before anything is stored. On error nothing is changed.
+    pub fn set_parameters(&mut self, values: Vec<(u64, String, Box<dyn Any + Send>)>)
+        -> Result<(), ParameterError> {
+        let mut blocks: HashMap<u64, HashMap<&str, &(dyn Any + Send)>> = HashMap::new();
+        for (block_id, parameter_name, value) in &values {
+            self.validate_parameter(parameter_name, value)?;
+            blocks.entry(*block_id).or_default().insert(parameter_name.as_str(), value.as_ref());
+        }
+        for (block_id, candidates) in &blocks {
+            self.check_constraints(*block_id, candidates)?;
+        }
+        let mut changed = Vec::with_capacity(values.len());
+        for (block_id, parameter_name, value) in values {
+            changed.push((block_id, parameter_name.clone()));
+            self.parameter_list.entry(block_id).or_default().insert(parameter_name, value);
+        }
+        for (block_id, parameter_name) in changed {
+            self.notify_listeners(&parameter_name, block_id);
+        }
+        Ok(())
+    }
+
+    pub fn get_parameter(&self, parameter_name: &str, block_id: u64) -> Option<&(dyn Any + Send)> {
+        self.parameter_list
+            .get(&block_id)
+            .and_then(|parameters| parameters.get(parameter_name))
+            .map(|value| value.as_ref())
+    }
+
+    pub fn get_parameter_value<T: Copy + 'static>(&self, parameter_name: &str, block_id: u64) -> Option<T> {
+        self.get_parameter(parameter_name, block_id)
+            .and_then(|value| value.downcast_ref::<T>())
+            .copied()
+    }
+
+    pub fn get_blocks(&self) -> Vec<u64> {
+        let mut blocks: Vec<u64> = self.parameter_list.keys().copied().collect();
+        blocks.sort_unstable();
+        blocks
+    }
+
+    pub fn get_block_parameters(&self, block_id: u64) -> Result<Vec<String>, ParameterError> {
+        let parameters = self.parameter_list.get(&block_id).ok_or(ParameterError::UnknownBlock(block_id))?;
+        let mut names: Vec<String> = parameters.keys().cloned().collect();
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn get_parameter_text(&self, parameter_name: &str, block_id: u64) -> Result<String, ParameterError> {
+        let parameters = self.parameter_list.get(&block_id).ok_or(ParameterError::UnknownBlock(block_id))?;
+        let value = parameters.get(parameter_name)
+            .ok_or(ParameterError::UnknownParameter(parameter_name.to_string()))?;
+        self.parameter_model_table.get(parameter_name)
+            .and_then(|parameter_model| parameter_model.format_value(value.as_ref()))
+            .ok_or(ParameterError::NoTextRepresentation(parameter_name.to_string()))
+    }
+
+    /// Parse `text` with the parameter model and store it like `set_parameter`.
+    pub fn set_parameter_text(&mut self, parameter_name: &str, block_id: u64, text: &str)
+        -> Result<(), ParameterError> {
+        let value = self.parse_parameter_text(parameter_name, text)?;
+        self.set_parameter(parameter_name, block_id, value)
+    }
+
+    pub fn parse_parameter_text(&self, parameter_name: &str, text: &str)
+        -> Result<Box<dyn Any + Send>, ParameterError> {
+        self.parameter_model_table.get(parameter_name)
+            .ok_or(ParameterError::UnknownParameter(parameter_name.to_string()))?
+            .parse_value(text)
+            .ok_or(ParameterError::InvalidValue(parameter_name.to_string()))
+    }
+
+    /// Canonical form of `text`: parsed, then formatted by the parameter
+    /// model, so e.g. "debug" and "Debug" give the same text.
+    pub fn normalize_parameter_text(&self, parameter_name: &str, text: &str) -> Result<String, ParameterError> {
+        let value = self.parse_parameter_text(parameter_name, text)?;
+        self.parameter_model_table.get(parameter_name)
+            .and_then(|parameter_model| parameter_model.format_value(value.as_ref()))
+            .ok_or(ParameterError::NoTextRepresentation(parameter_name.to_string()))
+    }
+
+    /// Export every registered parameter model as a JSON Schema document.
+    /// Cross-parameter constraints are listed under `x-constraints`.
+    pub fn export_schema(&self) -> String {
+        let mut names: Vec<&String> = self.parameter_model_table.keys().collect();
+        names.sort();
+
+        let mut properties = Vec::new();
+        for name in names {
+            let metadata = self.parameter_model_table[name].get_metadata();
+            let mut fields = vec![
+                format!("\"description\": {}", json_string(&metadata.description)),
+                format!("\"type\": \"{}\"", metadata.param_type.json_type()),
+                format!("\"x-parameter-type\": \"{:?}\"", metadata.param_type),
+            ];
+            if let Some(default_value) = &metadata.default_value {
+                fields.push(format!("\"default\": {}", default_value.to_json()));
+            }
+            if !metadata.allowed_values.is_empty() {
+                let allowed: Vec<String> = metadata.allowed_values.iter().map(|value| value.to_json()).collect();
+                fields.push(format!("\"enum\": [{}]", allowed.join(", ")));
+            }
+            let (min_key, max_key) = match metadata.param_type {
+                ParameterType::NUMBER => ("minimum", "maximum"),
+                _ => ("x-minimum", "x-maximum"),
+            };
+            if let Some(min_value) = &metadata.min_value {
+                fields.push(format!("\"{}\": {}", min_key, min_value.to_json()));
+            }
+            if let Some(max_value) = &metadata.max_value {
+                fields.push(format!("\"{}\": {}", max_key, max_value.to_json()));
+            }
+            if let Some(constraints) = self.parameter_constraint_table.get(name) {
+                if !constraints.is_empty() {
+                    let constraints: Vec<String> = constraints.iter().map(|constraint| {
+                        let parameters: Vec<String> = constraint.get_parameters().iter()
+                            .map(|parameter| json_string(parameter)).collect();
+                        format!("{{\"name\": {}, \"description\": {}, \"parameters\": [{}]}}",
+                                json_string(&constraint.get_name()),
+                                json_string(&constraint.get_description()),
+                                parameters.join(", "))
+                    }).collect();
+                    fields.push(format!("\"x-constraints\": [{}]", constraints.join(", ")));
+                }
+            }
+            properties.push(format!("    {}: {{\n      {}\n    }}", json_string(name), fields.join(",\n      ")));
+        }
+
+        format!("{{\n  \"$schema\": \"https://json-schema.org/draft/2020-12/schema\",\n  \"title\": \"Parameters\",\n  \"type\": \"object\",\n  \"properties\": {{\n{}\n  }}\n}}\n",
+                properties.join(",\n"))
+    }
+
+    fn notify_listeners(&mut self, parameter_name: &str, block_id: u64) {
+        let value = match self.parameter_list.get(&block_id).and_then(|parameters| parameters.get(parameter_name)) {
+            Some(value) => value.as_ref(),
+            None => return,
+        };
+        self.parameter_listeners.retain_mut(|listener| listener(parameter_name, block_id, value));
+    }
+
+    fn validate_parameter(&self, parameter_name: &str, value: &Box<dyn Any + Send>) -> Result<(), ParameterError> {
+        let parameter_model = self.parameter_model_table.get(parameter_name)
+            .ok_or(ParameterError::UnknownParameter(parameter_name.to_string()))?;
+        if !parameter_model.validate_value(value) {
+            return Err(ParameterError::InvalidValue(parameter_name.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Check the constraints touching `candidates` against the block values
+    /// overridden by `candidates`.
+    fn check_constraints(&self, block_id: u64, candidates: &HashMap<&str, &(dyn Any + Send)>)
+        -> Result<(), ParameterError> {
+        let mut values: HashMap<&str, &(dyn Any + Send)> = HashMap::new();
+        if let Some(parameters) = self.parameter_list.get(&block_id) {
+            for (name, value) in parameters {
+                values.insert(name.as_str(), value.as_ref());
+            }
+        }
+        values.extend(candidates.iter().map(|(name, value)| (*name, *value)));
+        let block_parameters = BlockParameters { values };
+
+        for constraint in self.parameter_constraint_table.values().flatten() {
+            if !constraint.get_parameters().iter().any(|name| candidates.contains_key(name.as_str())) {
+                continue;
+            }
+            if !constraint.is_satisfied(&block_parameters) {
+                return Err(ParameterError::ConstraintViolated(constraint.get_name()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for ParameterControl {
+    fn default() -> Self {
+        ParameterControl::new()
+    }
+}
+
+/// Shared handle on a `ParameterControl`.
+///
+/// Independent pipelines (and tests) create their own registry with
+/// `ParameterRegistry::new` and pass it to their blocks; code that doesn't
+/// care uses `ParameterRegistry::global`. The handle dereferences to the
+/// underlying `Mutex<ParameterControl>`.
+#[derive(Clone)]
+pub struct ParameterRegistry {
+    control: Arc<Mutex<ParameterControl>>,
+}
+
+impl ParameterRegistry {
+    pub fn new() -> Self {
+        ParameterRegistry {
+            control: Arc::new(Mutex::new(ParameterControl::new())),
+        }
+    }
+
+    pub fn global() -> Self {
+        ParameterRegistry {
+            control: global_control().clone(),
+        }
+    }
+
+    pub fn is_global(&self) -> bool {
+        PARAMETER_CONTROL.get().is_some_and(|global| Arc::ptr_eq(global, &self.control))
+    }
+
+    pub fn add_parameter_model(&self, parameter_model: Box<dyn ParameterModel + Send>) {
+        self.lock_control().add_parameter_model(parameter_model);
+    }
+
+    /// Same as the free function `add_parameter`, on this registry.
+    pub fn add_parameter(&self, parameter_name: String, block_id: u64, value: Box<dyn Any + Send>) -> bool {
+        match self.lock_control().set_parameter(&parameter_name, block_id, value) {
+            Ok(()) => true,
+            Err(ParameterError::UnknownParameter(name)) => panic!("Parameter {} not found", name),
+            Err(_) => false,
+        }
+    }
+
+    fn lock_control(&self) -> MutexGuard<'_, ParameterControl> {
+        self.control.lock().unwrap()
+    }
+}
+
+impl Default for ParameterRegistry {
+    fn default() -> Self {
+        ParameterRegistry::new()
+    }
+}
+
+impl std::ops::Deref for ParameterRegistry {
+    type Target = Mutex<ParameterControl>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.control
     }
 }
 
 pub fn add_parameter_model(parameter_model: Box<dyn ParameterModel + Send>)  {
-    let mut parameter_control = PARAMETER_CONTROL.get().expect("").lock().unwrap();
-    parameter_control.parameter_model_table.insert(parameter_model.get_name(), parameter_model);
+    ParameterRegistry::global().add_parameter_model(parameter_model);
 }
 
 pub fn add_parameter(parameter_name: String, block_id: u64, value: Box<dyn Any + Send>) -> bool {
-    let valid: bool;
-    let mut parameter_control = PARAMETER_CONTROL.get().expect("").lock().unwrap();
-    match parameter_control.parameter_model_table.get(&parameter_name) {
-        Some(parameter_model) => valid = parameter_model.validate_value(&value),
-        None => panic!("Parameter {} not found", parameter_name),
+    ParameterRegistry::global().add_parameter(parameter_name, block_id, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FftSizeParameter;
+    impl ParameterModel for FftSizeParameter {
+        fn get_name(&self) -> String {
+            "FftSize".to_string()
+        }
+        fn get_description(&self) -> String {
+            "Number of points of the FFT".to_string()
+        }
+        fn get_param_type(&self) -> ParameterType {
+            ParameterType::NUMBER
+        }
+        fn validate_value(&self, value: &Box<dyn Any + Send>) -> bool {
+            value.downcast_ref::<u32>().is_some_and(|size| size.is_power_of_two())
+        }
+        fn parse_value(&self, text: &str) -> Option<Box<dyn Any + Send>> {
+            text.parse::<u32>().ok().map(|size| Box::new(size) as Box<dyn Any + Send>)
+        }
+        fn format_value(&self, value: &(dyn Any + Send)) -> Option<String> {
+            value.downcast_ref::<u32>().map(|size| size.to_string())
+        }
+        fn get_constraints(&self) -> Vec<ParameterConstraint> {
+            vec![ParameterConstraint::between::<u32, u32, _>(
+                "FftSizeCoversWindow".to_string(),
+                "The FFT size must be greater or equal to the window length".to_string(),
+                "FftSize".to_string(),
+                "WindowLength".to_string(),
+                |fft_size, window_length| fft_size >= window_length,
+            )]
+        }
     }
-    if valid {
-        parameter_control.parameter_list.insert(block_id, value);
+
+    struct WindowLengthParameter;
+    impl ParameterModel for WindowLengthParameter {
+        fn get_name(&self) -> String {
+            "WindowLength".to_string()
+        }
+        fn get_description(&self) -> String {
+            "Number of samples of the analysis window".to_string()
+        }
+        fn get_param_type(&self) -> ParameterType {
+            ParameterType::NUMBER
+        }
+        fn validate_value(&self, value: &Box<dyn Any + Send>) -> bool {
+            value.downcast_ref::<u32>().is_some_and(|length| *length > 0)
+        }
+    }
+
+    fn registry_with_models() -> ParameterRegistry {
+        let registry = ParameterRegistry::new();
+        registry.add_parameter_model(Box::new(FftSizeParameter));
+        registry.add_parameter_model(Box::new(WindowLengthParameter));
+        registry
     }
-    valid
-}
\ No newline at end of file
+
+    #[test]
+    fn test_constraint_rejects_inconsistent_update() {
+        let registry = registry_with_models();
+        let block_id = 2601;
+        assert!(registry.add_parameter("FftSize".to_string(), block_id, Box::new(256u32)));
+        assert!(registry.add_parameter("WindowLength".to_string(), block_id, Box::new(200u32)));
+        assert!(!registry.add_parameter("WindowLength".to_string(), block_id, Box::new(300u32)));
+        assert!(!registry.add_parameter("FftSize".to_string(), block_id, Box::new(128u32)));
+
+        let parameter_control = registry.lock().unwrap();
+        assert_eq!(parameter_control.get_parameter_value::<u32>("FftSize", block_id), Some(256));
+        assert_eq!(parameter_control.get_parameter_value::<u32>("WindowLength", block_id), Some(200));
+    }
+
+    #[test]
+    fn test_constraint_is_checked_per_block() {
+        let registry = registry_with_models();
+        assert!(registry.add_parameter("WindowLength".to_string(), 2602, Box::new(1000u32)));
+        assert!(registry.add_parameter("FftSize".to_string(), 2603, Box::new(64u32)));
+
+        let mut parameter_control = registry.lock().unwrap();
+        assert_eq!(parameter_control.set_parameter("FftSize", 2602, Box::new(512u32)),
+                   Err(ParameterError::ConstraintViolated("FftSizeCoversWindow".to_string())));
+        assert_eq!(parameter_control.set_parameter("FftSize", 2602, Box::new(100u32)),
+                   Err(ParameterError::InvalidValue("FftSize".to_string())));
+        assert_eq!(parameter_control.set_parameter("FrameSize", 2602, Box::new(100u32)),
+                   Err(ParameterError::UnknownParameter("FrameSize".to_string())));
+        assert!(parameter_control.set_parameter("FftSize", 2602, Box::new(1024u32)).is_ok());
+    }
+
+    #[test]
+    fn test_set_parameters_checks_final_state() {
+        let registry = registry_with_models();
+        assert!(registry.add_parameter("FftSize".to_string(), 1, Box::new(256u32)));
+        assert!(registry.add_parameter("WindowLength".to_string(), 1, Box::new(200u32)));
+
+        let mut parameter_control = registry.lock().unwrap();
+        // Each value alone would violate FftSizeCoversWindow, together they don't.
+        assert!(parameter_control.set_parameters(vec![
+            (1, "FftSize".to_string(), Box::new(1024u32)),
+            (1, "WindowLength".to_string(), Box::new(1000u32)),
+        ]).is_ok());
+        assert_eq!(parameter_control.set_parameters(vec![
+            (1, "WindowLength".to_string(), Box::new(100u32)),
+            (1, "FftSize".to_string(), Box::new(64u32)),
+            (2, "FftSize".to_string(), Box::new(512u32)),
+        ]), Err(ParameterError::ConstraintViolated("FftSizeCoversWindow".to_string())));
+        assert_eq!(parameter_control.get_parameter_value::<u32>("WindowLength", 1), Some(1000));
+        assert_eq!(parameter_control.get_parameter_value::<u32>("FftSize", 2), None);
+    }
+
+    #[test]
+    fn test_listeners() {
+        let registry = ParameterRegistry::new();
+        registry.add_parameter_model(Box::new(FftSizeParameter));
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let recorded = changes.clone();
+        registry.lock().unwrap().add_listener(Box::new(move |name, block_id, value| {
+            let size = *value.downcast_ref::<u32>().unwrap();
+            recorded.lock().unwrap().push((name.to_string(), block_id, size));
+            size != 1024
+        }));
+
+        assert!(registry.add_parameter("FftSize".to_string(), 1, Box::new(256u32)));
+        assert!(!registry.add_parameter("FftSize".to_string(), 1, Box::new(100u32)));
+        assert!(registry.lock().unwrap().set_parameters(vec![(2, "FftSize".to_string(), Box::new(1024u32))]).is_ok());
+        assert!(registry.add_parameter("FftSize".to_string(), 3, Box::new(64u32)));
+        assert_eq!(*changes.lock().unwrap(), vec![("FftSize".to_string(), 1, 256), ("FftSize".to_string(), 2, 1024)]);
+    }
+
+    #[test]
+    fn test_scoped_registries_are_independent() {
+        let first = registry_with_models();
+        let second = registry_with_models();
+        assert!(!first.is_global());
+        assert!(ParameterRegistry::global().is_global());
+
+        assert!(first.add_parameter("FftSize".to_string(), 1, Box::new(64u32)));
+        assert!(second.add_parameter("WindowLength".to_string(), 1, Box::new(1000u32)));
+        assert!(!second.add_parameter("FftSize".to_string(), 1, Box::new(64u32)));
+
+        assert_eq!(first.lock().unwrap().get_parameter_value::<u32>("FftSize", 1), Some(64));
+        assert_eq!(first.lock().unwrap().get_parameter_value::<u32>("WindowLength", 1), None);
+        assert_eq!(second.lock().unwrap().get_parameter_value::<u32>("FftSize", 1), None);
+        assert_eq!(ParameterRegistry::global().lock().unwrap().get_parameter_value::<u32>("FftSize", 1), None);
+
+        let shared = first.clone();
+        assert!(shared.add_parameter("WindowLength".to_string(), 1, Box::new(32u32)));
+        assert_eq!(first.lock().unwrap().get_parameter_value::<u32>("WindowLength", 1), Some(32));
+    }
+
+    #[test]
+    fn test_parameter_text() {
+        let registry = registry_with_models();
+        let block_id = 2801;
+        let mut parameter_control = registry.lock().unwrap();
+        assert_eq!(parameter_control.get_parameter_text("FftSize", block_id),
+                   Err(ParameterError::UnknownBlock(block_id)));
+        assert!(parameter_control.set_parameter_text("FftSize", block_id, "512").is_ok());
+        assert_eq!(parameter_control.get_parameter_text("FftSize", block_id), Ok("512".to_string()));
+        assert_eq!(parameter_control.set_parameter_text("FftSize", block_id, "abc"),
+                   Err(ParameterError::InvalidValue("FftSize".to_string())));
+        assert!(parameter_control.set_parameter("WindowLength", block_id, Box::new(10u32)).is_ok());
+        assert_eq!(parameter_control.get_parameter_text("WindowLength", block_id),
+                   Err(ParameterError::NoTextRepresentation("WindowLength".to_string())));
+        assert_eq!(parameter_control.get_block_parameters(block_id),
+                   Ok(vec!["FftSize".to_string(), "WindowLength".to_string()]));
+        assert!(parameter_control.get_blocks().contains(&block_id));
+    }
+
+    #[test]
+    fn test_export_schema() {
+        let registry = registry_with_models();
+        crate::processor::logger::LogLevelParameter::with_registry(&registry);
+
+        let schema = registry.lock().unwrap().export_schema();
+        assert!(schema.starts_with("{\n  \"$schema\""));
+        assert!(schema.contains("\"FftSize\": {\n      \"description\": \"Number of points of the FFT\",\n      \"type\": \"number\""));
+        assert!(schema.contains("\"x-constraints\": [{\"name\": \"FftSizeCoversWindow\""));
+        assert!(schema.contains("\"default\": \"Error\""));
+        assert!(schema.contains("\"enum\": [\"Emergency\", \"Alert\", \"Critical\", \"Error\", \"Warning\", \"Notice\", \"Info\", \"Debug\"]"));
+        assert!(schema.contains("\"x-minimum\": \"Debug\""));
+        assert!(schema.contains("\"x-maximum\": \"Emergency\""));
+    }
+}
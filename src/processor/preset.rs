@@ -0,0 +1,275 @@
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet};
+use crate::processor::parameter::{ParameterControl, ParameterError};
+
+/// Named set of parameter values across blocks, e.g. "short-range" or
+/// "calibration".
+///
+/// Values are kept in their text representation (see
+/// `ParameterModel::parse_value`) so presets can be compared and diffed
+/// without knowing the parameter types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterPreset {
+    name: String,
+    values: BTreeMap<(u64, String), String>,
+}
+
+/// A value that differs between two presets. `None` means the parameter is
+/// not part of that preset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PresetDifference {
+    pub block_id: u64,
+    pub parameter_name: String,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+impl ParameterPreset {
+    pub fn new(name: String) -> Self {
+        ParameterPreset {
+            name,
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Snapshot the values currently held by `parameter_control`. Values
+    /// without a text representation can't be part of a preset, their
+    /// (block id, parameter name) are returned next to the preset.
+    pub fn capture(name: String, parameter_control: &ParameterControl) -> (Self, Vec<(u64, String)>) {
+        let mut preset = ParameterPreset::new(name);
+        let mut not_captured = Vec::new();
+        for block_id in parameter_control.get_blocks() {
+            for parameter_name in parameter_control.get_block_parameters(block_id).unwrap_or_default() {
+                match parameter_control.get_parameter_text(&parameter_name, block_id) {
+                    Ok(text) => preset.set_value(block_id, parameter_name, text),
+                    Err(_) => not_captured.push((block_id, parameter_name)),
+                }
+            }
+        }
+        (preset, not_captured)
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Store `value` as is, see `normalize` to bring it to its canonical form.
+    pub fn set_value(&mut self, block_id: u64, parameter_name: String, value: String) {
+        self.values.insert((block_id, parameter_name), value);
+    }
+
+    /// Rewrite every value in the canonical text of its parameter model.
+    /// Fails, leaving the preset unchanged, if a value can't be parsed.
+    pub fn normalize(&mut self, parameter_control: &ParameterControl) -> Result<(), ParameterError> {
+        let mut values = BTreeMap::new();
+        for ((block_id, parameter_name), text) in &self.values {
+            let text = parameter_control.normalize_parameter_text(parameter_name, text)?;
+            values.insert((*block_id, parameter_name.clone()), text);
+        }
+        self.values = values;
+        Ok(())
+    }
+
+    pub fn get_value(&self, block_id: u64, parameter_name: &str) -> Option<&String> {
+        self.values.get(&(block_id, parameter_name.to_string()))
+    }
+
+    pub fn remove_value(&mut self, block_id: u64, parameter_name: &str) -> Option<String> {
+        self.values.remove(&(block_id, parameter_name.to_string()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Parse and validate the whole preset, then apply it in one update.
+    /// If any value is rejected `parameter_control` is left unchanged.
+    pub fn apply(&self, parameter_control: &mut ParameterControl) -> Result<(), ParameterError> {
+        let mut values: Vec<(u64, String, Box<dyn Any + Send>)> = Vec::with_capacity(self.values.len());
+        for ((block_id, parameter_name), text) in &self.values {
+            let value = parameter_control.parse_parameter_text(parameter_name, text)?;
+            values.push((*block_id, parameter_name.clone(), value));
+        }
+        parameter_control.set_parameters(values)
+    }
+
+    /// Values that differ between `self` (left) and `other` (right), ordered
+    /// by block id then parameter name. Values are compared in their
+    /// canonical text (see `ParameterControl::normalize_parameter_text`),
+    /// or as is when `parameter_control` can't parse them.
+    pub fn diff(&self, other: &ParameterPreset, parameter_control: &ParameterControl) -> Vec<PresetDifference> {
+        let normalized = |key: &(u64, String), text: Option<&String>| {
+            text.map(|text| parameter_control.normalize_parameter_text(&key.1, text).unwrap_or_else(|_| text.clone()))
+        };
+        let keys: BTreeSet<&(u64, String)> = self.values.keys().chain(other.values.keys()).collect();
+        keys.into_iter()
+            .filter_map(|key| {
+                let left = normalized(key, self.values.get(key));
+                let right = normalized(key, other.values.get(key));
+                if left == right {
+                    return None;
+                }
+                Some(PresetDifference {
+                    block_id: key.0,
+                    parameter_name: key.1.clone(),
+                    left,
+                    right,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Collection of presets selectable by name, one per operating mode.
+#[derive(Clone, Debug, Default)]
+pub struct ParameterProfiles {
+    presets: BTreeMap<String, ParameterPreset>,
+}
+
+impl ParameterProfiles {
+    pub fn new() -> Self {
+        ParameterProfiles {
+            presets: BTreeMap::new(),
+        }
+    }
+
+    /// Add `preset`, replacing any preset with the same name.
+    pub fn add_preset(&mut self, preset: ParameterPreset) {
+        self.presets.insert(preset.get_name(), preset);
+    }
+
+    pub fn get_preset(&self, name: &str) -> Option<&ParameterPreset> {
+        self.presets.get(name)
+    }
+
+    pub fn remove_preset(&mut self, name: &str) -> Option<ParameterPreset> {
+        self.presets.remove(name)
+    }
+
+    pub fn get_preset_names(&self) -> Vec<String> {
+        self.presets.keys().cloned().collect()
+    }
+
+    pub fn apply(&self, name: &str, parameter_control: &mut ParameterControl) -> Result<(), ParameterError> {
+        match self.presets.get(name) {
+            Some(preset) => preset.apply(parameter_control),
+            None => Err(ParameterError::UnknownPreset(name.to_string())),
+        }
+    }
+
+    pub fn diff(&self, left: &str, right: &str, parameter_control: &ParameterControl)
+        -> Result<Vec<PresetDifference>, ParameterError> {
+        let left = self.presets.get(left).ok_or(ParameterError::UnknownPreset(left.to_string()))?;
+        let right = self.presets.get(right).ok_or(ParameterError::UnknownPreset(right.to_string()))?;
+        Ok(left.diff(right, parameter_control))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::logger::{LogLevel, Logger};
+    use crate::processor::parameter::{ParameterModel, ParameterRegistry, ParameterType};
+
+    /// Model without a text representation.
+    struct OpaqueParameter;
+    impl ParameterModel for OpaqueParameter {
+        fn get_name(&self) -> String {
+            "Opaque".to_string()
+        }
+        fn get_description(&self) -> String {
+            "Value only settable programmatically".to_string()
+        }
+        fn get_param_type(&self) -> ParameterType {
+            ParameterType::NUMBER
+        }
+        fn validate_value(&self, value: &Box<dyn Any + Send>) -> bool {
+            value.downcast_ref::<u32>().is_some()
+        }
+    }
+
+    fn preset(name: &str, values: &[(u64, &str)]) -> ParameterPreset {
+        let mut preset = ParameterPreset::new(name.to_string());
+        for (block_id, level) in values {
+            preset.set_value(*block_id, "LogLevel".to_string(), level.to_string());
+        }
+        preset
+    }
+
+    #[test]
+    fn test_apply_is_atomic() {
+        let registry = ParameterRegistry::new();
+        let _first = Logger::with_registry(&registry, 1, LogLevel::Error);
+        let _second = Logger::with_registry(&registry, 2, LogLevel::Error);
+
+        let calibration = preset("calibration", &[(1, "Debug"), (2, "Info")]);
+        assert!(calibration.apply(&mut registry.lock().unwrap()).is_ok());
+        assert_eq!(registry.lock().unwrap().get_parameter_value::<LogLevel>("LogLevel", 1), Some(LogLevel::Debug));
+        assert_eq!(registry.lock().unwrap().get_parameter_value::<LogLevel>("LogLevel", 2), Some(LogLevel::Info));
+
+        let broken = preset("broken", &[(1, "Warning"), (2, "Verbose")]);
+        assert_eq!(broken.apply(&mut registry.lock().unwrap()),
+                   Err(ParameterError::InvalidValue("LogLevel".to_string())));
+        assert_eq!(registry.lock().unwrap().get_parameter_value::<LogLevel>("LogLevel", 1), Some(LogLevel::Debug));
+
+        let (mut captured, not_captured) = ParameterPreset::capture("current".to_string(), &registry.lock().unwrap());
+        assert!(not_captured.is_empty());
+        assert_eq!(captured.remove_value(1, "LogRateLimit"), Some("off".to_string()));
+        assert_eq!(captured.remove_value(2, "LogRateLimit"), Some("off".to_string()));
+        assert!(captured.diff(&calibration, &registry.lock().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_normalized_values() {
+        let registry = ParameterRegistry::new();
+        let _logger = Logger::with_registry(&registry, 1, LogLevel::Error);
+        let parameter_control = registry.lock().unwrap();
+
+        let mut lower = preset("lower", &[(1, "debug"), (2, "WARNING")]);
+        assert!(lower.diff(&preset("upper", &[(1, "Debug"), (2, "Warning")]), &parameter_control).is_empty());
+        assert!(lower.normalize(&parameter_control).is_ok());
+        assert_eq!(lower.get_value(1, "LogLevel"), Some(&"Debug".to_string()));
+
+        let mut broken = preset("broken", &[(1, "info"), (2, "Verbose")]);
+        assert_eq!(broken.normalize(&parameter_control), Err(ParameterError::InvalidValue("LogLevel".to_string())));
+        assert_eq!(broken.get_value(1, "LogLevel"), Some(&"info".to_string()));
+    }
+
+    #[test]
+    fn test_capture_reports_values_without_text() {
+        let registry = ParameterRegistry::new();
+        let _logger = Logger::with_registry(&registry, 1, LogLevel::Error);
+        registry.add_parameter_model(Box::new(OpaqueParameter));
+        assert!(registry.add_parameter("Opaque".to_string(), 1, Box::new(7u32)));
+
+        let (captured, not_captured) = ParameterPreset::capture("current".to_string(), &registry.lock().unwrap());
+        assert_eq!(not_captured, vec![(1, "Opaque".to_string())]);
+        assert_eq!(captured.get_value(1, "LogLevel"), Some(&"Error".to_string()));
+    }
+
+    #[test]
+    fn test_profiles_diff() {
+        let mut profiles = ParameterProfiles::new();
+        profiles.add_preset(preset("short-range", &[(1, "Error"), (2, "Warning")]));
+        profiles.add_preset(preset("long-range", &[(1, "Error"), (3, "Notice")]));
+        assert_eq!(profiles.get_preset_names(), vec!["long-range".to_string(), "short-range".to_string()]);
+
+        let registry = ParameterRegistry::new();
+        let _logger = Logger::with_registry(&registry, 1, LogLevel::Error);
+        let parameter_control = registry.lock().unwrap();
+        let differences = profiles.diff("short-range", "long-range", &parameter_control).unwrap();
+        assert_eq!(differences, vec![
+            PresetDifference { block_id: 2, parameter_name: "LogLevel".to_string(),
+                               left: Some("Warning".to_string()), right: None },
+            PresetDifference { block_id: 3, parameter_name: "LogLevel".to_string(),
+                               left: None, right: Some("Notice".to_string()) },
+        ]);
+        assert_eq!(profiles.diff("short-range", "idle", &parameter_control), Err(ParameterError::UnknownPreset("idle".to_string())));
+        assert_eq!(profiles.apply("idle", &mut ParameterControl::new()),
+                   Err(ParameterError::UnknownPreset("idle".to_string())));
+    }
+}
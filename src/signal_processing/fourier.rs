@@ -1,42 +1,41 @@
 use num_traits::{Float, Zero};
-use crate::gmath::complex::{Complex, ComplexTrait};
+use crate::gmath::complex::Complex;
 use crate::gmath::numbers::factorize;
-pub fn dft<T> (signal: &Vec<Complex<T>>) -> Vec<Complex<T>> 
+pub fn dft<T> (signal: &[Complex<T>]) -> Vec<Complex<T>> 
 where
     T: Float + Into<f64> + Copy + Zero
     + std::fmt::Display + std::fmt::Debug
-    + std::convert::From<f64>
 {
     let n = signal.len();
-    let mut result = vec![Complex::<T>::new(T::zero(), T::zero()); n];
+    let mut result = vec![Complex::<T>::zero(); n];
     for k in 0..n {
         for t in 0..n {
-            let angle: T = <f64 as Into<T>>::into(-2.0 * std::f64::consts::PI * (k as f64 * t as f64 / n as f64));
-            result[k] += signal[t] * Complex::from_polar(<f64 as Into<T>>::into(1.0), angle);
+            let angle: T = T::from(-2.0 * std::f64::consts::PI * (k as f64 * t as f64 / n as f64)).unwrap();
+            result[k] += signal[t] * Complex::from_polar(T::one(), angle);
         }
     }
     result
 }
 
-pub fn idft<T> (signal: &Vec<Complex<T>>) -> Vec<Complex<T>> 
+pub fn idft<T> (signal: &[Complex<T>]) -> Vec<Complex<T>> 
 where
     T: Float + Into<f64> + Copy + Zero
     + std::fmt::Display + std::fmt::Debug
-    + std::convert::From<f64>
 {
     let n = signal.len();
-    let mut result = vec![Complex::<T>::new(T::zero(), T::zero()); n];
+    let scale = T::from(n).unwrap();
+    let mut result = vec![Complex::<T>::zero(); n];
     for k in 0..n {
         for t in 0..n {
-            let angle : T = <f64 as Into<T>>::into(2.0 * std::f64::consts::PI * (k as f64 * t as f64 / n as f64));
-            result[k] += signal[t] * Complex::from_polar(<f64 as Into<T>>::into(1.0), angle);
+            let angle : T = T::from(2.0 * std::f64::consts::PI * (k as f64 * t as f64 / n as f64)).unwrap();
+            result[k] += signal[t] * Complex::from_polar(T::one(), angle);
         }
-        result[k] /= Complex::<T>::new(<f64 as Into<T>>::into(n as f64), T::zero());
+        result[k] /= scale;
     }
     result
 } 
 
-fn fft_core<T>( signal: &Vec<Complex<T>>,
+fn fft_core<T>( signal: &[Complex<T>],
                 size: usize, 
                 mut factors: Vec<u32>, 
                 start: usize,
@@ -45,7 +44,6 @@ fn fft_core<T>( signal: &Vec<Complex<T>>,
 where
     T: Float + Into<f64> + Copy + Zero
     + std::fmt::Display + std::fmt::Debug
-    + std::convert::From<f64>
 {
     if size == 1 || factors.len() == 0 {
         return std::vec![signal[start].clone()];
@@ -55,10 +53,10 @@ where
     let remaining = factors.drain(1..).collect::<Vec<u32>>();
     let mut chunks: Vec<Vec<Complex<T>>> = Vec::new();
     for i in 0..chunk_number {
-        let chunk_fft = fft_core(&signal, chunk_size, remaining.clone(), start + i * step, step * chunk_number, reverse);
+        let chunk_fft = fft_core(signal, chunk_size, remaining.clone(), start + i * step, step * chunk_number, reverse);
         chunks.push(chunk_fft);
     }
-    let mut result = vec![Complex::<T>::new(T::zero(), T::zero()); size];
+    let mut result = vec![Complex::<T>::zero(); size];
     let mut rotation_factor = -2.0 * std::f64::consts::PI;
     if reverse {
         rotation_factor *= -1.0;
@@ -66,19 +64,18 @@ where
     for k in 0..size {
         for i in 0..chunk_number as usize {
             let index_sel = k % chunk_size as usize;
-            let angle: T = <f64 as Into<T>>::into(rotation_factor * (k as f64 * i as f64 / size as f64));
-            result[k] += chunks[i][index_sel] * Complex::from_polar(<f64 as Into<T>>::into(1.0), angle);
+            let angle: T = T::from(rotation_factor * (k as f64 * i as f64 / size as f64)).unwrap();
+            result[k] += chunks[i][index_sel] * Complex::from_polar(T::one(), angle);
         }
     }
     result
 }
 
-pub fn fft<T>(signal: &Vec<Complex<T>>, 
+pub fn fft<T>(signal: &[Complex<T>], 
                 size: Option<usize>) -> Vec<Complex<T>> 
 where
     T: Float + Into<f64> + Copy + Zero
     + std::fmt::Display + std::fmt::Debug
-    + std::convert::From<f64>
 {
     let fft_size = size.unwrap_or(signal.len());
     
@@ -88,12 +85,11 @@ where
     fft_core(signal, fft_size, factors, 0, 1, false)
 }
 
-pub fn ifft<T>(signal: &Vec<Complex<T>>, 
+pub fn ifft<T>(signal: &[Complex<T>], 
                 size: Option<usize>) -> Vec<Complex<T>> 
 where
     T: Float + Into<f64> + Copy + Zero
     + std::fmt::Display + std::fmt::Debug
-    + std::convert::From<f64>
 {
     let fft_size = size.unwrap_or(signal.len());
     
@@ -102,8 +98,9 @@ where
     // Call the core FFT function here
     let mut result = fft_core(signal, fft_size, factors, 0, 1, true);
 
-    for i in 0..fft_size {
-        result[i] /= Complex::<T>::new(<f64 as Into<T>>::into(fft_size as f64), T::zero());
+    let scale = T::from(fft_size).unwrap();
+    for value in result.iter_mut() {
+        *value /= scale;
     }
     result
 }
@@ -112,10 +109,9 @@ pub fn fft2<T>(signal: &mut Vec<Vec<Complex<T>>>, size: Option<(usize, usize)>)
 where
     T: Float + Into<f64> + Copy + Zero
     + std::fmt::Display + std::fmt::Debug
-    + std::convert::From<f64>
 {
     let (rows, cols) = size.unwrap_or((signal.len(), signal[0].len()));
-    let mut result = vec![vec![Complex::<T>::new(T::zero(), T::zero()); cols]; rows];
+    let mut result = vec![vec![Complex::<T>::zero(); cols]; rows];
     
     for i in 0..rows {
         let row_fft = fft(signal.get_mut(i).unwrap(), Some(cols));
@@ -137,10 +133,9 @@ pub fn ifft2<T>(signal: &mut Vec<Vec<Complex<T>>>, size: Option<(usize, usize)>)
 where
     T: Float + Into<f64> + Copy + Zero
     + std::fmt::Display + std::fmt::Debug
-    + std::convert::From<f64>
 {
     let (rows, cols) = size.unwrap_or((signal.len(), signal[0].len()));
-    let mut result = vec![vec![Complex::<T>::new(T::zero(), T::zero()); cols]; rows];
+    let mut result = vec![vec![Complex::<T>::zero(); cols]; rows];
     
     
     for j in 0..cols {
@@ -160,6 +155,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gmath::complex::{ComplexTrait, from_interleaved, from_iq_i16};
     use rand::Rng;
     use std::time::{Duration,Instant};
 
@@ -216,4 +212,18 @@ mod tests {
             assert!((result[i].imag - signal[i].imag).abs() < 1e-10);
         }
     }
+
+    #[test]
+    fn test_fft_from_interleaved_samples() {
+        let samples: [i16; 8] = [16384, 0, 0, 16384, -16384, 0, 0, -16384];
+        let signal = from_iq_i16(&samples, 1.0 / 32768.0);
+        let result = fft(&signal, None);
+        let buffer = [0.5f32, 0.0, 0.0, 0.5, -0.5, 0.0, 0.0, -0.5];
+        assert_eq!(fft(from_interleaved(&buffer).unwrap(), None), result);
+        // A tone at bin 1 of amplitude 0.5.
+        for (bin, value) in result.iter().enumerate() {
+            let expected = if bin == 1 { 2.0 } else { 0.0 };
+            assert!((value.real - expected).abs() < 1e-6 && value.imag.abs() < 1e-6);
+        }
+    }
 }
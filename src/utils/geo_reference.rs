@@ -6,6 +6,7 @@ const FLATTENING: f64 = 1.0/298.257223563;
 const ECCENTRICITY_SQUARE: f64 = FLATTENING * (2.0 - FLATTENING);
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RAEPoint<T: Float> {
     pub range: T,
     pub azimuth: T,
@@ -13,6 +14,7 @@ pub struct RAEPoint<T: Float> {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct XYZPoint<T: Float> {
     pub x: T,
     pub y: T,
@@ -20,6 +22,7 @@ pub struct XYZPoint<T: Float> {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ECEFPoint<T: Float> {
     pub x_ecef: T,
     pub y_ecef: T,
@@ -27,6 +30,7 @@ pub struct ECEFPoint<T: Float> {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LLAPoint<T: Float> {
     pub latitude: T,
     pub longitude: T,
@@ -236,3 +240,16 @@ impl <T: Float + std::ops::Mul<Output = T>> From<ECEFPoint<T>> for LLAPoint<T> w
     }
 }
 
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serde() {
+        let point = LLAPoint { latitude: 48.85, longitude: 2.35, altitude: 35.0 };
+        let json = serde_json::to_string(&point).unwrap();
+        assert_eq!(json, r#"{"latitude":48.85,"longitude":2.35,"altitude":35.0}"#);
+        let decoded: LLAPoint<f64> = serde_json::from_str(&json).unwrap();
+        assert_eq!((decoded.latitude, decoded.longitude, decoded.altitude), (48.85, 2.35, 35.0));
+    }
+}
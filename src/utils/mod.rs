@@ -1,2 +1,3 @@
 pub mod geo_reference;
-pub mod times;
\ No newline at end of file
+pub mod times;
+pub mod json;
\ No newline at end of file
@@ -0,0 +1,53 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Civil date (year, month, day) of a number of days since 1970-01-01.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// RFC 3339 UTC representation with microseconds, e.g. `2024-05-01T12:00:00.000000Z`.
+pub fn format_rfc3339(time: SystemTime) -> String {
+    let (seconds, micros) = match time.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => (elapsed.as_secs() as i64, elapsed.subsec_micros()),
+        Err(before) => {
+            let before = before.duration();
+            let mut seconds = -(before.as_secs() as i64);
+            let mut micros = before.subsec_micros();
+            if micros > 0 {
+                seconds -= 1;
+                micros = 1_000_000 - micros;
+            }
+            (seconds, micros)
+        }
+    };
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+    let second_of_day = seconds.rem_euclid(86400);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
+            year, month, day,
+            second_of_day / 3600, (second_of_day % 3600) / 60, second_of_day % 60,
+            micros)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_rfc3339() {
+        assert_eq!(format_rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00.000000Z");
+        let time = UNIX_EPOCH + Duration::from_micros(951_827_696_123_456);
+        assert_eq!(format_rfc3339(time), "2000-02-29T12:34:56.123456Z");
+        let time = UNIX_EPOCH - Duration::from_micros(500_000);
+        assert_eq!(format_rfc3339(time), "1969-12-31T23:59:59.500000Z");
+    }
+}